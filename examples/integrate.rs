@@ -12,9 +12,9 @@ use smithay::{
     input::{
         keyboard::{FilterResult, XkbConfig},
         pointer::{AxisFrame, ButtonEvent, MotionEvent},
+        touch::{DownEvent, MotionEvent as TouchMotionEvent, UpEvent},
         SeatHandler, SeatState,
     },
-    reexports::wayland_server::protocol::wl_surface::WlSurface,
     utils::{Rectangle, Transform, SERIAL_COUNTER},
 };
 use smithay_egui::EguiState;
@@ -34,7 +34,7 @@ struct State(SeatState<State>);
 impl SeatHandler for State {
     type KeyboardFocus = EguiState;
     type PointerFocus = EguiState;
-    type TouchFocus = WlSurface;
+    type TouchFocus = EguiState;
     fn seat_state(&mut self) -> &mut SeatState<Self> {
         &mut self.0
     }
@@ -43,6 +43,8 @@ impl SeatHandler for State {
 fn main() -> Result<()> {
     // setup logger
     tracing_subscriber::fmt().compact().init();
+    // the timestamps of winit's input events are relative to the creation of the backend
+    let start_time = std::time::Instant::now();
     // create a winit-backend
     let (mut backend, mut input) =
         winit::init::<GlowRenderer>().map_err(|_| anyhow::anyhow!("Winit failed to start"))?;
@@ -58,15 +60,20 @@ fn main() -> Result<()> {
     let mut seat = seat_state.new_seat("seat-0");
     let mut state = State(seat_state);
     let keyboard = seat.add_keyboard(XkbConfig::default(), 200, 25)?;
+    // egui does its own text processing and needs to know about the keymap of the seat
+    egui.set_xkb_config(XkbConfig::default())?;
+    // and generates its own key repeats
+    egui.set_key_repeat(200, 25);
     keyboard.set_focus(&mut state, Some(egui.clone()), SERIAL_COUNTER.next_serial());
     let pointer = seat.add_pointer();
+    let touch = seat.add_touch();
 
     loop {
         input.dispatch_new_events(|event| {
             use smithay::backend::{
                 input::{
                     AbsolutePositionEvent, Axis, AxisSource, Event, InputEvent, KeyboardKeyEvent,
-                    PointerAxisEvent, PointerButtonEvent,
+                    PointerAxisEvent, PointerButtonEvent, TouchEvent,
                 },
                 winit::WinitEvent::*,
             };
@@ -75,7 +82,7 @@ fn main() -> Result<()> {
                 Input(event) => match event {
                     // egui tracks pointers
                     InputEvent::DeviceAdded { device } => egui.handle_device_added(&device),
-                    InputEvent::DeviceRemoved { device } => egui.handle_device_added(&device),
+                    InputEvent::DeviceRemoved { device } => egui.handle_device_removed(&device),
                     // we rely on the filter-closure of the keyboard.input call to get the values we need for egui.
                     //
                     // NOTE: usually you would need to check `EguiState::wants_keyboard_input` or track focus of egui
@@ -97,6 +104,7 @@ fn main() -> Result<()> {
                         let pos = event.position();
                         pointer.motion(
                             &mut state,
+                            // the focus is located at the top-left corner of egui's area
                             Some((egui.clone(), (0., 0.).into())),
                             &MotionEvent {
                                 location: (pos.x, pos.y).into(),
@@ -149,14 +157,55 @@ fn main() -> Result<()> {
                                 frame = frame.stop(Axis::Vertical);
                             }
                             pointer.axis(&mut state, frame);
+                            pointer.frame(&mut state);
                         }
                     }
+                    // Touch events are handled the same way as pointer events,
+                    // egui additionally emulates a pointer for the first touch point.
+                    InputEvent::TouchDown { event } => {
+                        let pos = event.position();
+                        touch.down(
+                            &mut state,
+                            Some((egui.clone(), (0., 0.).into())),
+                            &DownEvent {
+                                slot: event.slot(),
+                                location: (pos.x, pos.y).into(),
+                                serial: SERIAL_COUNTER.next_serial(),
+                                time: event.time_msec(),
+                            },
+                        );
+                    }
+                    InputEvent::TouchMotion { event } => {
+                        let pos = event.position();
+                        touch.motion(
+                            &mut state,
+                            Some((egui.clone(), (0., 0.).into())),
+                            &TouchMotionEvent {
+                                slot: event.slot(),
+                                location: (pos.x, pos.y).into(),
+                                time: event.time_msec(),
+                            },
+                        );
+                    }
+                    InputEvent::TouchUp { event } => touch.up(
+                        &mut state,
+                        &UpEvent {
+                            slot: event.slot(),
+                            serial: SERIAL_COUNTER.next_serial(),
+                            time: event.time_msec(),
+                        },
+                    ),
+                    InputEvent::TouchCancel { .. } => touch.cancel(&mut state),
+                    InputEvent::TouchFrame { .. } => touch.frame(&mut state),
                     _ => {}
                 },
                 _ => {}
             }
         });
 
+        // emit repeated key events for held keys
+        egui.handle_key_repeat(start_time.elapsed().as_millis() as u32);
+
         let size = backend.window_size();
         // Here we compute the rendered egui frame
         let egui_frame: TextureRenderElement<GlesTexture> = egui
@@ -170,6 +219,8 @@ fn main() -> Result<()> {
                 1.0,
             )
             .expect("Failed to render egui");
+        // keep egui's drags alive, even when the pointer leaves its area
+        egui.start_pointer_grab(&pointer, &mut state, SERIAL_COUNTER.next_serial());
 
         // Lastly put the rendered frame on the screen
         backend.bind()?;
@@ -0,0 +1,198 @@
+// This file transfers egui's clipboard contents to and from wayland clients, see lib.rs for usage
+
+use egui::{text_edit::TextEditState, Context, Id, Key, Modifiers};
+use smithay::{
+    reexports::rustix::pipe::pipe, wayland::selection::data_device::SelectionRequestError,
+};
+
+use std::{
+    fmt,
+    fs::File,
+    io::{self, Read, Write},
+    os::fd::OwnedFd,
+};
+
+/// Mime types offered for text copied in egui, the first one is also used to request text from clients.
+pub const TEXT_MIME_TYPES: &[&str] = &[
+    "text/plain;charset=utf-8",
+    "text/plain",
+    "UTF8_STRING",
+    "TEXT",
+    "STRING",
+];
+
+#[derive(Debug, Default)]
+pub struct Selection {
+    // text most recently copied in egui
+    pub text: Option<String>,
+    // if `text` still needs to be published as a selection
+    pub dirty: bool,
+    pub paste_requested: bool,
+}
+
+impl Selection {
+    pub fn copy(&mut self, text: String) {
+        self.text = Some(text);
+        self.dirty = true;
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Selections {
+    pub clipboard: Selection,
+    pub primary: Selection,
+    // if the text selected in egui should be queried for the primary selection
+    pub query_primary: bool,
+    // text range selected in egui after the last frame
+    pub selected: Option<(Id, usize, usize)>,
+}
+
+impl Selections {
+    /// Queries the primary selection on the next frame, if egui selected different text.
+    pub fn update_selected(&mut self, ctx: &Context) {
+        // only the final selection of a drag is of interest
+        if ctx.input(|input| input.pointer.any_down()) {
+            return;
+        }
+        let selected = text_selection(ctx);
+        if selected != self.selected {
+            self.selected = selected;
+            self.query_primary |= selected.is_some();
+        }
+    }
+}
+
+/// The non-empty text range selected in the focused text edit of egui
+pub fn text_selection(ctx: &Context) -> Option<(Id, usize, usize)> {
+    let id = ctx.memory(|mem| mem.focused())?;
+    let range = TextEditState::load(ctx, id)?.cursor.char_range()?;
+    let start = range.primary.index.min(range.secondary.index);
+    let end = range.primary.index.max(range.secondary.index);
+    (start != end).then_some((id, start, end))
+}
+
+pub fn is_cut_command(modifiers: Modifiers, key: Key) -> bool {
+    key == Key::Cut || (modifiers.command && key == Key::X)
+}
+
+pub fn is_copy_command(modifiers: Modifiers, key: Key) -> bool {
+    key == Key::Copy || (modifiers.command && key == Key::C)
+}
+
+pub fn is_paste_command(modifiers: Modifiers, key: Key) -> bool {
+    key == Key::Paste || (modifiers.command && key == Key::V)
+}
+
+pub fn is_text_mime_type(mime_type: &str) -> bool {
+    TEXT_MIME_TYPES.contains(&mime_type)
+}
+
+pub fn text_mime_types() -> Vec<String> {
+    TEXT_MIME_TYPES
+        .iter()
+        .map(|mime_type| mime_type.to_string())
+        .collect()
+}
+
+#[derive(Debug)]
+pub enum RequestError {
+    Pipe(io::Error),
+    Selection(SelectionRequestError),
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestError::Pipe(err) => write!(f, "Failed to create pipe: {}", err),
+            RequestError::Selection(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+// hands the write end of a new pipe to `request` for the first text mime type
+// the selection offers and returns the read end on success
+pub fn request_text(
+    mut request: impl FnMut(String, OwnedFd) -> Result<(), SelectionRequestError>,
+) -> Result<File, RequestError> {
+    for mime_type in TEXT_MIME_TYPES {
+        let (reader, writer) = pipe().map_err(|err| RequestError::Pipe(err.into()))?;
+        match request(mime_type.to_string(), writer) {
+            Ok(()) => return Ok(File::from(reader)),
+            Err(SelectionRequestError::InvalidMimetype) => continue,
+            Err(err) => return Err(RequestError::Selection(err)),
+        }
+    }
+    Err(RequestError::Selection(
+        SelectionRequestError::InvalidMimetype,
+    ))
+}
+
+// clients may take their time to transfer data, so neither side may block the compositor
+
+pub fn read_text(mut reader: File, callback: impl FnOnce(String) + Send + 'static) {
+    std::thread::spawn(move || {
+        let mut buffer = Vec::new();
+        match reader.read_to_end(&mut buffer) {
+            Ok(_) => callback(String::from_utf8_lossy(&buffer).into_owned()),
+            Err(err) => log::warn!("Failed to read selection: {}", err),
+        }
+    });
+}
+
+pub fn write_text(fd: OwnedFd, text: String) {
+    std::thread::spawn(move || {
+        if let Err(err) = File::from(fd).write_all(text.as_bytes()) {
+            log::warn!("Failed to send selection: {}", err);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a client selection offering `offered`, records the requested mime types
+    fn request(
+        offered: &'static [&'static str],
+        requested: &mut Vec<String>,
+    ) -> Result<File, RequestError> {
+        request_text(|mime_type, _fd| {
+            requested.push(mime_type.clone());
+            if offered.contains(&mime_type.as_str()) {
+                Ok(())
+            } else {
+                Err(SelectionRequestError::InvalidMimetype)
+            }
+        })
+    }
+
+    #[test]
+    fn requests_offered_mime_type() {
+        let mut requested = Vec::new();
+        assert!(request(&["UTF8_STRING", "text/plain"], &mut requested).is_ok());
+        assert_eq!(
+            requested,
+            ["text/plain;charset=utf-8", "text/plain"].map(String::from)
+        );
+    }
+
+    #[test]
+    fn request_errors_are_kept() {
+        let mut requested = Vec::new();
+        assert!(matches!(
+            request(&["image/png"], &mut requested),
+            Err(RequestError::Selection(
+                SelectionRequestError::InvalidMimetype
+            ))
+        ));
+        assert_eq!(requested.len(), TEXT_MIME_TYPES.len());
+
+        let result = request_text(|_, _| Err(SelectionRequestError::ServerSideSelection));
+        assert!(matches!(
+            result,
+            Err(RequestError::Selection(
+                SelectionRequestError::ServerSideSelection
+            ))
+        ));
+    }
+}
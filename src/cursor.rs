@@ -0,0 +1,275 @@
+// This file renders egui's cursor icons from an XCursor theme, see lib.rs for usage
+
+use smithay::{
+    backend::{
+        allocator::Fourcc,
+        renderer::{
+            element::{
+                memory::MemoryRenderBuffer,
+                texture::{TextureBuffer, TextureRenderElement},
+                Kind,
+            },
+            gles::{GlesError, GlesTexture},
+            glow::GlowRenderer,
+        },
+    },
+    input::pointer::CursorImageStatus,
+    utils::{Logical, Point, Transform},
+};
+use xcursor::parser::{parse_xcursor, Image};
+
+use std::{collections::HashMap, fmt};
+
+use crate::input::convert_cursor_icon;
+
+/// Returns the cursor-shape-v1 name of an [`egui::CursorIcon`].
+///
+/// Returns `None` for [`egui::CursorIcon::None`], which hides the cursor.
+pub fn cursor_shape_name(icon: egui::CursorIcon) -> Option<&'static str> {
+    match convert_cursor_icon(icon) {
+        CursorImageStatus::Named(icon) => Some(icon.name()),
+        _ => None,
+    }
+}
+
+/// A loaded cursor image
+#[derive(Debug, Clone)]
+pub struct CursorBuffer {
+    /// Pixels of the cursor
+    pub buffer: MemoryRenderBuffer,
+    /// Position of the hotspot relative to the top-left corner of the buffer
+    pub hotspot: Point<i32, Logical>,
+}
+
+/// Cursor icons of egui loaded from an XCursor theme on disk
+pub struct XCursorTheme {
+    theme: xcursor::CursorTheme,
+    size: u32,
+    // images of every shape, `None` if the theme is missing a shape
+    images: HashMap<&'static str, Option<Vec<Image>>>,
+    // loaded frames by shape, buffer scale and frame index
+    buffers: HashMap<(&'static str, i32, usize), CursorBuffer>,
+    textures: HashMap<(&'static str, i32, usize), TextureBuffer<GlesTexture>>,
+}
+
+impl fmt::Debug for XCursorTheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("XCursorTheme")
+            .field("theme", &self.theme)
+            .field("size", &self.size)
+            .field("images", &self.images.keys().collect::<Vec<_>>())
+            .field("buffers", &self.buffers)
+            .field("textures", &self.textures.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl XCursorTheme {
+    /// Loads the theme `name` using the nominal cursor `size` in logical pixels.
+    pub fn load(name: &str, size: u32) -> XCursorTheme {
+        XCursorTheme {
+            theme: xcursor::CursorTheme::load(name),
+            size,
+            images: HashMap::new(),
+            buffers: HashMap::new(),
+            textures: HashMap::new(),
+        }
+    }
+
+    /// Loads the theme set by `XCURSOR_THEME` and `XCURSOR_SIZE`,
+    /// falling back to the "default" theme with a size of 24.
+    pub fn from_env() -> XCursorTheme {
+        let name = std::env::var("XCURSOR_THEME").unwrap_or_else(|_| "default".into());
+        let size = std::env::var("XCURSOR_SIZE")
+            .ok()
+            .and_then(|size| size.parse().ok())
+            .unwrap_or(24);
+        Self::load(&name, size)
+    }
+
+    /// Returns the image of `icon` for an output of the given `scale`.
+    ///
+    /// `time` in milliseconds selects the frame of animated cursors.
+    /// Returns `None` if the cursor is hidden or the theme has no matching image.
+    pub fn buffer(
+        &mut self,
+        icon: egui::CursorIcon,
+        scale: f64,
+        time: u32,
+    ) -> Option<CursorBuffer> {
+        let name = cursor_shape_name(icon)?;
+        let int_scale = scale.ceil() as i32;
+        let idx = self.frame(name, int_scale, time)?;
+
+        // pixels are only copied for frames not loaded yet
+        let key = (name, int_scale, idx);
+        if !self.buffers.contains_key(&key) {
+            let image = self.image(name, idx);
+            let buffer_scale = buffer_scale(image, self.size);
+            let buffer = CursorBuffer {
+                buffer: MemoryRenderBuffer::from_slice(
+                    &image.pixels_rgba,
+                    Fourcc::Argb8888,
+                    (image.width as i32, image.height as i32),
+                    buffer_scale,
+                    Transform::Normal,
+                    None,
+                ),
+                hotspot: hotspot(image, buffer_scale),
+            };
+            self.buffers.insert(key, buffer);
+        }
+        self.buffers.get(&key).cloned()
+    }
+
+    /// Produce a [`TextureRenderElement`] of `icon` with its hotspot at `location`.
+    ///
+    /// The element can be drawn alongside the one returned by [`crate::EguiState::render`].
+    /// `time` in milliseconds selects the frame of animated cursors.
+    pub fn render_element(
+        &mut self,
+        renderer: &mut GlowRenderer,
+        icon: egui::CursorIcon,
+        location: Point<f64, Logical>,
+        scale: f64,
+        alpha: f32,
+        time: u32,
+    ) -> Result<Option<TextureRenderElement<GlesTexture>>, GlesError> {
+        let Some(name) = cursor_shape_name(icon) else {
+            return Ok(None);
+        };
+        let int_scale = scale.ceil() as i32;
+        let Some(idx) = self.frame(name, int_scale, time) else {
+            return Ok(None);
+        };
+        let image = self.image(name, idx);
+        let buffer_scale = buffer_scale(image, self.size);
+        let hotspot = hotspot(image, buffer_scale);
+
+        let key = (name, int_scale, idx);
+        let texture = match self.textures.get(&key) {
+            Some(texture) => texture.clone(),
+            None => {
+                let texture = TextureBuffer::from_memory(
+                    renderer,
+                    &image.pixels_rgba,
+                    Fourcc::Argb8888,
+                    (image.width as i32, image.height as i32),
+                    false,
+                    buffer_scale,
+                    Transform::Normal,
+                    None,
+                )?;
+                self.textures.insert(key, texture.clone());
+                texture
+            }
+        };
+
+        let location = location - hotspot.to_f64();
+        Ok(Some(TextureRenderElement::from_texture_buffer(
+            location.to_physical(scale),
+            &texture,
+            Some(alpha),
+            None,
+            None,
+            Kind::Cursor,
+        )))
+    }
+
+    // selects the frame of the image closest to the requested size, returns its index
+    fn frame(&mut self, name: &'static str, scale: i32, time: u32) -> Option<usize> {
+        let theme = &self.theme;
+        let images = self
+            .images
+            .entry(name)
+            .or_insert_with(|| load_images(theme, name))
+            .as_ref()?;
+
+        let size = self.size * scale as u32;
+        let nearest = images
+            .iter()
+            .min_by_key(|image| (size as i32 - image.size as i32).abs())?
+            .size;
+        let frames = images
+            .iter()
+            .enumerate()
+            .filter(|(_, image)| image.size == nearest)
+            .collect::<Vec<_>>();
+
+        let total = frames.iter().map(|(_, image)| image.delay).sum::<u32>();
+        let mut time = if total == 0 { 0 } else { time % total };
+        for (idx, image) in &frames {
+            if time < image.delay {
+                return Some(*idx);
+            }
+            time -= image.delay;
+        }
+        frames.first().map(|(idx, _)| *idx)
+    }
+
+    // an image previously returned by `frame`
+    fn image(&self, name: &'static str, idx: usize) -> &Image {
+        &self.images[name].as_ref().unwrap()[idx]
+    }
+}
+
+fn hotspot(image: &Image, scale: i32) -> Point<i32, Logical> {
+    (image.xhot as i32 / scale, image.yhot as i32 / scale).into()
+}
+
+fn load_images(theme: &xcursor::CursorTheme, name: &'static str) -> Option<Vec<Image>> {
+    // older themes only ship the legacy X11 names
+    let alt_names = match convert_cursor_icon(egui_icon_for(name)?) {
+        CursorImageStatus::Named(icon) => icon.alt_names(),
+        _ => &[],
+    };
+    let images = std::iter::once(name)
+        .chain(alt_names.iter().copied())
+        .filter_map(|name| theme.load_icon(name))
+        .find_map(|path| {
+            std::fs::read(&path)
+                .ok()
+                .and_then(|content| parse_xcursor(&content))
+        });
+    if images.is_none() {
+        log::warn!("Cursor theme has no image for {}", name);
+    }
+    images
+}
+
+fn egui_icon_for(name: &str) -> Option<egui::CursorIcon> {
+    egui::CursorIcon::ALL
+        .iter()
+        .copied()
+        .find(|icon| cursor_shape_name(*icon) == Some(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(size: u32) -> Image {
+        Image {
+            size,
+            width: size,
+            height: size,
+            xhot: size / 2,
+            yhot: size / 4,
+            delay: 0,
+            pixels_rgba: Vec::new(),
+            pixels_argb: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn scale_follows_image_size() {
+        assert_eq!(buffer_scale(&image(24), 24), 1);
+        assert_eq!(buffer_scale(&image(48), 24), 2);
+        assert_eq!(buffer_scale(&image(64), 32), 2);
+        // e.g. picked at scale 2 from a theme lacking larger images
+        assert_eq!(buffer_scale(&image(16), 24), 1);
+
+        let image = image(48);
+        assert_eq!(hotspot(&image, buffer_scale(&image, 24)), (12, 6).into());
+    }
+}
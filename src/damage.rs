@@ -0,0 +1,233 @@
+// This file computes the damage between two egui frames, see lib.rs for usage
+
+use egui::{
+    epaint::{ClippedPrimitive, Mesh, Primitive},
+    Rect, TextureId, TexturesDelta,
+};
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, VecDeque},
+    hash::{Hash, Hasher},
+};
+
+// more rectangles than this are merged into their bounding box
+const MAX_DAMAGE_RECTS: usize = 16;
+
+/// Summary of a tessellated primitive, cheap enough to keep around until the next frame
+#[derive(Debug, Clone, Copy)]
+pub struct PrimitiveDigest {
+    // identifies what the primitive draws, `None` if that can't be compared between frames
+    hash: Option<u64>,
+    // area drawn to in egui points
+    rect: Rect,
+}
+
+pub fn digest(
+    primitives: &[ClippedPrimitive],
+    textures_delta: &TexturesDelta,
+) -> Vec<PrimitiveDigest> {
+    let updated = textures_delta
+        .set
+        .iter()
+        .map(|(id, _)| *id)
+        .collect::<Vec<TextureId>>();
+
+    primitives
+        .iter()
+        .map(|clipped| {
+            let (hash, bounds) = match &clipped.primitive {
+                Primitive::Mesh(mesh) => (
+                    // meshes sampling from an updated texture draw something new
+                    (!updated.contains(&mesh.texture_id))
+                        .then(|| hash_mesh(clipped.clip_rect, mesh)),
+                    mesh.calc_bounds(),
+                ),
+                Primitive::Callback(callback) => (None, callback.rect),
+            };
+            PrimitiveDigest {
+                hash,
+                rect: bounds.intersect(clipped.clip_rect),
+            }
+        })
+        .filter(|digest| digest.rect.is_positive())
+        .collect()
+}
+
+/// Rectangles in egui points that differ between the frames described by `previous` and `current`
+///
+/// Primitives are matched in paint order, so a primitive moving above or below another
+/// one is damaged, even if it looks the same.
+pub fn diff(previous: &[PrimitiveDigest], current: &[PrimitiveDigest]) -> Vec<Rect> {
+    let mut positions = HashMap::<u64, VecDeque<usize>>::new();
+    for (idx, digest) in previous.iter().enumerate() {
+        if let Some(hash) = digest.hash {
+            positions.entry(hash).or_default().push_back(idx);
+        }
+    }
+
+    let mut matched = vec![false; previous.len()];
+    let mut next = 0;
+    let mut damage = Vec::new();
+    for digest in current {
+        let found = digest
+            .hash
+            .and_then(|hash| positions.get_mut(&hash))
+            .and_then(|queue| {
+                // skip occurrences painted before the last match
+                while queue.front().is_some_and(|idx| *idx < next) {
+                    queue.pop_front();
+                }
+                queue.pop_front()
+            });
+        match found {
+            Some(idx) => {
+                matched[idx] = true;
+                next = idx + 1;
+            }
+            None => damage.push(digest.rect),
+        }
+    }
+    damage.extend(
+        previous
+            .iter()
+            .zip(matched)
+            .filter(|(_, matched)| !matched)
+            .map(|(digest, _)| digest.rect),
+    );
+
+    if damage.len() > MAX_DAMAGE_RECTS {
+        let bounds = damage
+            .iter()
+            .fold(Rect::NOTHING, |acc, rect| acc.union(*rect));
+        damage = vec![bounds];
+    }
+    damage
+}
+
+fn hash_mesh(clip_rect: Rect, mesh: &Mesh) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for coord in [
+        clip_rect.min.x,
+        clip_rect.min.y,
+        clip_rect.max.x,
+        clip_rect.max.y,
+    ] {
+        coord.to_bits().hash(&mut hasher);
+    }
+    mesh.texture_id.hash(&mut hasher);
+    mesh.indices.hash(&mut hasher);
+    for vertex in &mesh.vertices {
+        for coord in [vertex.pos.x, vertex.pos.y, vertex.uv.x, vertex.uv.y] {
+            coord.to_bits().hash(&mut hasher);
+        }
+        vertex.color.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use egui::{epaint::ImageDelta, Color32, ColorImage, Pos2, TextureOptions, Vec2};
+
+    fn rect(x: f32, y: f32) -> Rect {
+        Rect::from_min_size(Pos2::new(x, y), Vec2::splat(10.0))
+    }
+
+    fn mesh(rect: Rect, texture_id: TextureId) -> ClippedPrimitive {
+        let mut mesh = Mesh::with_texture(texture_id);
+        mesh.add_rect_with_uv(
+            rect,
+            Rect::from_min_max(Pos2::ZERO, Pos2::ZERO),
+            Color32::RED,
+        );
+        ClippedPrimitive {
+            clip_rect: Rect::from_min_size(Pos2::ZERO, Vec2::splat(1000.0)),
+            primitive: Primitive::Mesh(mesh),
+        }
+    }
+
+    fn frame(rects: &[Rect]) -> Vec<ClippedPrimitive> {
+        rects
+            .iter()
+            .map(|rect| mesh(*rect, TextureId::default()))
+            .collect()
+    }
+
+    fn damage(previous: &[ClippedPrimitive], current: &[ClippedPrimitive]) -> Vec<Rect> {
+        let delta = TexturesDelta::default();
+        diff(&digest(previous, &delta), &digest(current, &delta))
+    }
+
+    #[test]
+    fn identical_frames() {
+        let frame = frame(&[rect(0.0, 0.0), rect(20.0, 0.0), rect(0.0, 0.0)]);
+        assert!(damage(&frame, &frame).is_empty());
+    }
+
+    #[test]
+    fn moved_mesh() {
+        let previous = frame(&[rect(0.0, 0.0), rect(20.0, 0.0)]);
+        let current = frame(&[rect(0.0, 0.0), rect(40.0, 20.0)]);
+        assert_eq!(
+            damage(&previous, &current),
+            [rect(40.0, 20.0), rect(20.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn reordered_mesh() {
+        // `a` now paints above `b`, which might overlap it
+        let (a, b) = (rect(0.0, 0.0), rect(5.0, 5.0));
+        let damage = damage(&frame(&[a, b]), &frame(&[b, a]));
+        assert_eq!(damage, [a, a]);
+    }
+
+    #[test]
+    fn updated_texture() {
+        let texture_id = TextureId::Managed(1);
+        let frame = [
+            mesh(rect(0.0, 0.0), TextureId::default()),
+            mesh(rect(20.0, 0.0), texture_id),
+        ];
+        let mut delta = TexturesDelta::default();
+        delta.set.push((
+            texture_id,
+            ImageDelta::full(
+                ColorImage::new([1, 1], Color32::WHITE),
+                TextureOptions::default(),
+            ),
+        ));
+        let previous = digest(&frame, &TexturesDelta::default());
+        // the mesh sampling from the texture is replaced by itself
+        assert_eq!(
+            diff(&previous, &digest(&frame, &delta)),
+            [rect(20.0, 0.0), rect(20.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn many_rects_are_merged() {
+        let rects = (0..MAX_DAMAGE_RECTS)
+            .map(|i| rect(i as f32 * 20.0, 0.0))
+            .collect::<Vec<_>>();
+        let down = |rect: &Rect| rect.translate(Vec2::new(0.0, 20.0));
+
+        // every moved mesh damages its old and its new position
+        let mut half_moved = rects.clone();
+        for rect in &mut half_moved[..MAX_DAMAGE_RECTS / 2] {
+            *rect = down(rect);
+        }
+        let kept = damage(&frame(&rects), &frame(&half_moved));
+        assert_eq!(kept.len(), MAX_DAMAGE_RECTS);
+
+        let moved = rects.iter().map(down).collect::<Vec<_>>();
+        let merged = damage(&frame(&rects), &frame(&moved));
+        let bounds = rects
+            .iter()
+            .chain(&moved)
+            .fold(Rect::NOTHING, |acc, rect| acc.union(*rect));
+        assert_eq!(merged, [bounds]);
+    }
+}
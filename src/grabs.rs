@@ -0,0 +1,246 @@
+// This file contains grabs routing seat input to egui, see lib.rs for usage
+
+use smithay::{
+    backend::input::KeyState,
+    input::{
+        keyboard::{
+            GrabStartData as KeyboardGrabStartData, KeyboardGrab, KeyboardInnerHandle,
+            ModifiersState,
+        },
+        pointer::{
+            AxisFrame, ButtonEvent, GestureHoldBeginEvent, GestureHoldEndEvent,
+            GesturePinchBeginEvent, GesturePinchEndEvent, GesturePinchUpdateEvent,
+            GestureSwipeBeginEvent, GestureSwipeEndEvent, GestureSwipeUpdateEvent,
+            GrabStartData as PointerGrabStartData, MotionEvent, PointerGrab, PointerInnerHandle,
+            RelativeMotionEvent,
+        },
+        SeatHandler,
+    },
+    utils::{Logical, Point, Serial},
+};
+use xkbcommon::xkb::Keycode;
+
+use std::fmt;
+
+use crate::EguiState;
+
+/// [`PointerGrab`] keeping the pointer focus on egui until all buttons are released
+///
+/// Started by [`EguiState::start_pointer_grab`] while egui reports an active drag,
+/// so that the release is not sent to whatever surface the pointer ends up above.
+pub struct EguiPointerGrab<D: SeatHandler> {
+    egui: EguiState,
+    start_data: PointerGrabStartData<D>,
+}
+
+impl<D: SeatHandler> fmt::Debug for EguiPointerGrab<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EguiPointerGrab")
+            .field("egui", &self.egui)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<D: SeatHandler> EguiPointerGrab<D> {
+    pub(crate) fn new(egui: EguiState, start_data: PointerGrabStartData<D>) -> Self {
+        EguiPointerGrab { egui, start_data }
+    }
+}
+
+impl<D: SeatHandler + 'static> PointerGrab<D> for EguiPointerGrab<D> {
+    fn motion(
+        &mut self,
+        data: &mut D,
+        handle: &mut PointerInnerHandle<'_, D>,
+        _focus: Option<(<D as SeatHandler>::PointerFocus, Point<f64, Logical>)>,
+        event: &MotionEvent,
+    ) {
+        // keep sending motion to egui, even outside of its area
+        handle.motion(data, self.start_data.focus.clone(), event);
+    }
+
+    fn relative_motion(
+        &mut self,
+        data: &mut D,
+        handle: &mut PointerInnerHandle<'_, D>,
+        _focus: Option<(<D as SeatHandler>::PointerFocus, Point<f64, Logical>)>,
+        event: &RelativeMotionEvent,
+    ) {
+        handle.relative_motion(data, self.start_data.focus.clone(), event);
+    }
+
+    fn button(
+        &mut self,
+        data: &mut D,
+        handle: &mut PointerInnerHandle<'_, D>,
+        event: &ButtonEvent,
+    ) {
+        handle.button(data, event);
+        if handle.current_pressed().is_empty() {
+            // the drag is over, give the focus back to whatever is below the pointer
+            handle.unset_grab(self, data, event.serial, event.time, true);
+        }
+    }
+
+    fn axis(&mut self, data: &mut D, handle: &mut PointerInnerHandle<'_, D>, details: AxisFrame) {
+        handle.axis(data, details);
+    }
+
+    fn frame(&mut self, data: &mut D, handle: &mut PointerInnerHandle<'_, D>) {
+        handle.frame(data);
+    }
+
+    fn gesture_swipe_begin(
+        &mut self,
+        data: &mut D,
+        handle: &mut PointerInnerHandle<'_, D>,
+        event: &GestureSwipeBeginEvent,
+    ) {
+        handle.gesture_swipe_begin(data, event);
+    }
+
+    fn gesture_swipe_update(
+        &mut self,
+        data: &mut D,
+        handle: &mut PointerInnerHandle<'_, D>,
+        event: &GestureSwipeUpdateEvent,
+    ) {
+        handle.gesture_swipe_update(data, event);
+    }
+
+    fn gesture_swipe_end(
+        &mut self,
+        data: &mut D,
+        handle: &mut PointerInnerHandle<'_, D>,
+        event: &GestureSwipeEndEvent,
+    ) {
+        handle.gesture_swipe_end(data, event);
+    }
+
+    fn gesture_pinch_begin(
+        &mut self,
+        data: &mut D,
+        handle: &mut PointerInnerHandle<'_, D>,
+        event: &GesturePinchBeginEvent,
+    ) {
+        handle.gesture_pinch_begin(data, event);
+    }
+
+    fn gesture_pinch_update(
+        &mut self,
+        data: &mut D,
+        handle: &mut PointerInnerHandle<'_, D>,
+        event: &GesturePinchUpdateEvent,
+    ) {
+        handle.gesture_pinch_update(data, event);
+    }
+
+    fn gesture_pinch_end(
+        &mut self,
+        data: &mut D,
+        handle: &mut PointerInnerHandle<'_, D>,
+        event: &GesturePinchEndEvent,
+    ) {
+        handle.gesture_pinch_end(data, event);
+    }
+
+    fn gesture_hold_begin(
+        &mut self,
+        data: &mut D,
+        handle: &mut PointerInnerHandle<'_, D>,
+        event: &GestureHoldBeginEvent,
+    ) {
+        handle.gesture_hold_begin(data, event);
+    }
+
+    fn gesture_hold_end(
+        &mut self,
+        data: &mut D,
+        handle: &mut PointerInnerHandle<'_, D>,
+        event: &GestureHoldEndEvent,
+    ) {
+        handle.gesture_hold_end(data, event);
+    }
+
+    fn start_data(&self) -> &PointerGrabStartData<D> {
+        &self.start_data
+    }
+
+    fn unset(&mut self, _data: &mut D) {
+        self.egui.inner.lock().unwrap().pointer_grab = false;
+    }
+}
+
+/// [`KeyboardGrab`] routing all keyboard input to egui, e.g. for modal dialogs
+///
+/// Started by [`EguiState::start_keyboard_grab`] and released by [`EguiState::stop_keyboard_grab`]
+/// or automatically, once egui stops [wanting keyboard input](EguiState::wants_keyboard)
+/// after having wanted it during the grab.
+/// The focus egui took over is restored on release.
+pub struct EguiKeyboardGrab<D: SeatHandler> {
+    egui: EguiState,
+    start_data: KeyboardGrabStartData<D>,
+    // if egui wanted keyboard input at any point of the grab
+    wanted_keyboard: bool,
+}
+
+impl<D: SeatHandler> fmt::Debug for EguiKeyboardGrab<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EguiKeyboardGrab")
+            .field("egui", &self.egui)
+            .field("wanted_keyboard", &self.wanted_keyboard)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<D: SeatHandler> EguiKeyboardGrab<D> {
+    pub(crate) fn new(egui: EguiState, start_data: KeyboardGrabStartData<D>) -> Self {
+        let wanted_keyboard = egui.wants_keyboard();
+        EguiKeyboardGrab {
+            egui,
+            start_data,
+            wanted_keyboard,
+        }
+    }
+}
+
+impl<D: SeatHandler + 'static> KeyboardGrab<D> for EguiKeyboardGrab<D> {
+    fn input(
+        &mut self,
+        data: &mut D,
+        handle: &mut KeyboardInnerHandle<'_, D>,
+        keycode: Keycode,
+        state: KeyState,
+        modifiers: Option<ModifiersState>,
+        serial: Serial,
+        time: u32,
+    ) {
+        let wants_keyboard = self.egui.wants_keyboard();
+        // dialogs without text input never want the keyboard, only release once egui let go of it
+        if self.wanted_keyboard && !wants_keyboard {
+            // the modal is gone, hand the input back before processing the key
+            handle.set_focus(data, self.start_data.focus.clone(), serial);
+            handle.unset_grab(self, data, serial, true);
+        }
+        self.wanted_keyboard |= wants_keyboard;
+        handle.input(data, keycode, state, modifiers, serial, time);
+    }
+
+    fn set_focus(
+        &mut self,
+        _data: &mut D,
+        _handle: &mut KeyboardInnerHandle<'_, D>,
+        _focus: Option<<D as SeatHandler>::KeyboardFocus>,
+        _serial: Serial,
+    ) {
+        // egui keeps the focus for as long as the grab is active
+    }
+
+    fn start_data(&self) -> &KeyboardGrabStartData<D> {
+        &self.start_data
+    }
+
+    fn unset(&mut self, _data: &mut D) {
+        self.egui.inner.lock().unwrap().keyboard_grab = false;
+    }
+}
@@ -1,18 +1,22 @@
 // This file is a light wrapper around libxkbcommon, see the other file for usage
 
-use egui::{Key, Modifiers, PointerButton};
+use egui::{CursorIcon as EguiCursorIcon, Key, Modifiers, PointerButton};
 use smithay::{
     backend::input::MouseButton,
-    input::keyboard::{Keysym as KeysymU32, ModifiersState},
+    input::{
+        keyboard::{Keysym as KeysymU32, ModifiersState, XkbConfig},
+        pointer::{CursorIcon, CursorImageStatus},
+    },
 };
 use xkbcommon::xkb;
 pub use xkbcommon::xkb::{Keycode, Keysym};
 
-use std::convert::TryFrom;
+use std::{convert::TryFrom, ffi::OsString, io, path::Path};
 
 pub struct KbdInternal {
     keymap: xkb::Keymap,
     state: xkb::State,
+    compose: Option<xkb::compose::State>,
 }
 // SAFETY: This is OK, because all parts of xkb will remain on the same thread
 unsafe impl Send for KbdInternal {}
@@ -23,24 +27,63 @@ impl std::fmt::Debug for KbdInternal {
         f.debug_struct("KbdInternal")
             .field("keymap", &self.keymap.get_raw_ptr())
             .field("state", &self.state.get_raw_ptr())
+            .field(
+                "compose",
+                &self.compose.as_ref().map(|compose| compose.get_raw_ptr()),
+            )
             .finish()
     }
 }
 
 impl KbdInternal {
-    pub fn new() -> Option<KbdInternal> {
+    pub fn new(config: &XkbConfig<'_>) -> Option<KbdInternal> {
         let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
         let keymap = xkb::Keymap::new_from_names(
             &context,
-            "",
-            "",
-            "",
-            "",
-            None,
+            config.rules,
+            config.model,
+            config.layout,
+            config.variant,
+            config.options.clone(),
             xkb::KEYMAP_COMPILE_NO_FLAGS,
         )?;
         let state = xkb::State::new(&keymap);
-        Some(KbdInternal { keymap, state })
+        Some(KbdInternal {
+            keymap,
+            state,
+            compose: compose_table_from_locale()
+                .map(|table| xkb::compose::State::new(&table, xkb::compose::STATE_NO_FLAGS)),
+        })
+    }
+
+    pub fn from_string(keymap: String) -> Option<KbdInternal> {
+        let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
+        let keymap = xkb::Keymap::new_from_string(
+            &context,
+            keymap,
+            xkb::KEYMAP_FORMAT_TEXT_V1,
+            xkb::KEYMAP_COMPILE_NO_FLAGS,
+        )?;
+        let state = xkb::State::new(&keymap);
+        Some(KbdInternal {
+            keymap,
+            state,
+            compose: compose_table_from_locale()
+                .map(|table| xkb::compose::State::new(&table, xkb::compose::STATE_NO_FLAGS)),
+        })
+    }
+
+    // overwrite the modifier and layout state with the one of the seat
+    pub fn update_modifiers(&mut self, modifiers: &ModifiersState) {
+        let mods = modifiers.serialized;
+        self.state.update_mask(
+            mods.depressed,
+            mods.latched,
+            mods.locked,
+            0,
+            0,
+            mods.layout_effective,
+        );
     }
 
     // return true if modifier state has changed
@@ -54,9 +97,94 @@ impl KbdInternal {
         self.state.update_key(Keycode::new(keycode), direction);
     }
 
-    pub fn get_utf8(&self, keycode: u32) -> String {
-        self.state.key_get_utf8(Keycode::new(keycode))
+    pub fn key_repeats(&self, keycode: u32) -> bool {
+        self.keymap.key_repeats(Keycode::new(keycode))
+    }
+
+    pub fn set_compose_table(&mut self, table: Option<&xkb::compose::Table>) {
+        self.compose =
+            table.map(|table| xkb::compose::State::new(table, xkb::compose::STATE_NO_FLAGS));
+    }
+
+    // carry over the compose table of a previous keymap
+    pub fn take_compose(&mut self, other: KbdInternal) {
+        self.set_compose_table(
+            other
+                .compose
+                .map(|compose| compose.compose_table())
+                .as_ref(),
+        );
     }
+
+    pub fn reset_compose(&mut self) {
+        if let Some(compose) = self.compose.as_mut() {
+            compose.reset();
+        }
+    }
+
+    // returns the text generated by a key press, taking dead keys and compose sequences into account
+    pub fn key_text(&mut self, keycode: u32) -> Option<String> {
+        let keycode = Keycode::new(keycode);
+
+        if let Some(compose) = self.compose.as_mut() {
+            let sym = self.state.key_get_one_sym(keycode);
+            if compose.feed(sym) == xkb::compose::FeedResult::Accepted {
+                match compose.status() {
+                    xkb::compose::Status::Composing => return None,
+                    xkb::compose::Status::Composed => {
+                        let text = compose.utf8();
+                        compose.reset();
+                        return text.and_then(printable);
+                    }
+                    xkb::compose::Status::Cancelled => {
+                        compose.reset();
+                        return None;
+                    }
+                    xkb::compose::Status::Nothing => {}
+                }
+            }
+        }
+
+        printable(self.state.key_get_utf8(keycode))
+    }
+}
+
+// egui only expects printable characters in text events, control characters are handled as keys
+fn printable(text: String) -> Option<String> {
+    let text = text.chars().filter(|c| !c.is_control()).collect::<String>();
+    (!text.is_empty()).then_some(text)
+}
+
+fn compose_locale() -> OsString {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .filter_map(std::env::var_os)
+        .find(|locale| !locale.is_empty())
+        .unwrap_or_else(|| OsString::from("C"))
+}
+
+fn compose_table_from_locale() -> Option<xkb::compose::Table> {
+    let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
+    xkb::compose::Table::new_from_locale(
+        &context,
+        &compose_locale(),
+        xkb::compose::COMPILE_NO_FLAGS,
+    )
+    .ok()
+}
+
+/// Load a compose table from a file in the XCompose format.
+pub fn compose_table_from_file(path: &Path) -> io::Result<xkb::compose::Table> {
+    let buffer = std::fs::read(path)?;
+    let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
+    xkb::compose::Table::new_from_buffer(
+        &context,
+        buffer,
+        &compose_locale().to_string_lossy(),
+        xkb::compose::FORMAT_TEXT_V1,
+        xkb::compose::COMPILE_NO_FLAGS,
+    )
+    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Failed to parse compose file"))
 }
 
 /// Converts a set of raw keycodes into [`egui::Key`], if possible.
@@ -69,6 +197,140 @@ pub fn convert_key(keys: impl Iterator<Item = KeysymU32>) -> Option<Key> {
     None
 }
 
+/// Converts the keysyms and raw keycode of a key event into its [`egui::Key`] and physical key.
+///
+/// Like egui-winit the key falls back to the physical key, so that shortcuts keep working
+/// on layouts without latin letters. Returns `None` if egui knows neither of them.
+pub fn convert_key_event(
+    keys: impl Iterator<Item = KeysymU32>,
+    code: Keycode,
+) -> Option<(Key, Option<Key>)> {
+    let physical_key = convert_physical_key(code);
+    let key = convert_key(keys).or(physical_key)?;
+    Some((key, physical_key))
+}
+
+/// Converts a raw keycode into the layout-independent [`egui::Key`] at that position
+/// on an US keyboard, if possible.
+///
+/// Note: The keycode is expected to be offset by 8 like [`smithay::input::keyboard::KeysymHandle::raw_code`].
+pub fn convert_physical_key(code: Keycode) -> Option<Key> {
+    use egui::Key::*;
+
+    // evdev codes as defined in linux/input-event-codes.h
+    Some(match code.raw().checked_sub(8)? {
+        1 => Escape,        // KEY_ESC
+        2 => Num1,          // KEY_1
+        3 => Num2,          // KEY_2
+        4 => Num3,          // KEY_3
+        5 => Num4,          // KEY_4
+        6 => Num5,          // KEY_5
+        7 => Num6,          // KEY_6
+        8 => Num7,          // KEY_7
+        9 => Num8,          // KEY_8
+        10 => Num9,         // KEY_9
+        11 => Num0,         // KEY_0
+        12 => Minus,        // KEY_MINUS
+        13 => Equals,       // KEY_EQUAL
+        14 => Backspace,    // KEY_BACKSPACE
+        15 => Tab,          // KEY_TAB
+        16 => Q,            // KEY_Q
+        17 => W,            // KEY_W
+        18 => E,            // KEY_E
+        19 => R,            // KEY_R
+        20 => T,            // KEY_T
+        21 => Y,            // KEY_Y
+        22 => U,            // KEY_U
+        23 => I,            // KEY_I
+        24 => O,            // KEY_O
+        25 => P,            // KEY_P
+        26 => OpenBracket,  // KEY_LEFTBRACE
+        27 => CloseBracket, // KEY_RIGHTBRACE
+        28 => Enter,        // KEY_ENTER
+        30 => A,            // KEY_A
+        31 => S,            // KEY_S
+        32 => D,            // KEY_D
+        33 => F,            // KEY_F
+        34 => G,            // KEY_G
+        35 => H,            // KEY_H
+        36 => J,            // KEY_J
+        37 => K,            // KEY_K
+        38 => L,            // KEY_L
+        39 => Semicolon,    // KEY_SEMICOLON
+        40 => Quote,        // KEY_APOSTROPHE
+        41 => Backtick,     // KEY_GRAVE
+        43 => Backslash,    // KEY_BACKSLASH
+        44 => Z,            // KEY_Z
+        45 => X,            // KEY_X
+        46 => C,            // KEY_C
+        47 => V,            // KEY_V
+        48 => B,            // KEY_B
+        49 => N,            // KEY_N
+        50 => M,            // KEY_M
+        51 => Comma,        // KEY_COMMA
+        52 => Period,       // KEY_DOT
+        53 => Slash,        // KEY_SLASH
+        57 => Space,        // KEY_SPACE
+        59 => F1,           // KEY_F1
+        60 => F2,           // KEY_F2
+        61 => F3,           // KEY_F3
+        62 => F4,           // KEY_F4
+        63 => F5,           // KEY_F5
+        64 => F6,           // KEY_F6
+        65 => F7,           // KEY_F7
+        66 => F8,           // KEY_F8
+        67 => F9,           // KEY_F9
+        68 => F10,          // KEY_F10
+        71 => Num7,         // KEY_KP7
+        72 => Num8,         // KEY_KP8
+        73 => Num9,         // KEY_KP9
+        74 => Minus,        // KEY_KPMINUS
+        75 => Num4,         // KEY_KP4
+        76 => Num5,         // KEY_KP5
+        77 => Num6,         // KEY_KP6
+        78 => Plus,         // KEY_KPPLUS
+        79 => Num1,         // KEY_KP1
+        80 => Num2,         // KEY_KP2
+        81 => Num3,         // KEY_KP3
+        82 => Num0,         // KEY_KP0
+        83 => Period,       // KEY_KPDOT
+        87 => F11,          // KEY_F11
+        88 => F12,          // KEY_F12
+        96 => Enter,        // KEY_KPENTER
+        98 => Slash,        // KEY_KPSLASH
+        102 => Home,        // KEY_HOME
+        103 => ArrowUp,     // KEY_UP
+        104 => PageUp,      // KEY_PAGEUP
+        105 => ArrowLeft,   // KEY_LEFT
+        106 => ArrowRight,  // KEY_RIGHT
+        107 => End,         // KEY_END
+        108 => ArrowDown,   // KEY_DOWN
+        109 => PageDown,    // KEY_PAGEDOWN
+        110 => Insert,      // KEY_INSERT
+        111 => Delete,      // KEY_DELETE
+        117 => Equals,      // KEY_KPEQUAL
+        121 => Comma,       // KEY_KPCOMMA
+        133 => Copy,        // KEY_COPY
+        135 => Paste,       // KEY_PASTE
+        137 => Cut,         // KEY_CUT
+        183 => F13,         // KEY_F13
+        184 => F14,         // KEY_F14
+        185 => F15,         // KEY_F15
+        186 => F16,         // KEY_F16
+        187 => F17,         // KEY_F17
+        188 => F18,         // KEY_F18
+        189 => F19,         // KEY_F19
+        190 => F20,         // KEY_F20
+        191 => F21,         // KEY_F21
+        192 => F22,         // KEY_F22
+        193 => F23,         // KEY_F23
+        194 => F24,         // KEY_F24
+        _ => {
+            return None;
+        }
+    })
+}
+
 pub struct KeysymConv(pub KeysymU32);
 
 impl TryFrom<KeysymConv> for Key {
@@ -79,57 +341,115 @@ impl TryFrom<KeysymConv> for Key {
 
         #[allow(non_upper_case_globals)]
         Ok(match sym.0 {
-            Keysym::Down => ArrowDown,
-            Keysym::Left => ArrowLeft,
-            Keysym::Right => ArrowRight,
-            Keysym::Up => ArrowUp,
+            // Commands
+            Keysym::Down | Keysym::KP_Down => ArrowDown,
+            Keysym::Left | Keysym::KP_Left => ArrowLeft,
+            Keysym::Right | Keysym::KP_Right => ArrowRight,
+            Keysym::Up | Keysym::KP_Up => ArrowUp,
             Keysym::Escape => Escape,
-            Keysym::Tab => Tab,
+            Keysym::Tab | Keysym::ISO_Left_Tab | Keysym::KP_Tab => Tab,
             Keysym::BackSpace => Backspace,
-            Keysym::Return => Enter,
-            Keysym::space => Space,
-            Keysym::Insert => Insert,
-            Keysym::Delete => Delete,
-            Keysym::Home => Home,
-            Keysym::End => End,
-            Keysym::Page_Up => PageUp,
-            Keysym::Page_Down => PageDown,
-            Keysym::_0 => Num0,
-            Keysym::_1 => Num1,
-            Keysym::_2 => Num2,
-            Keysym::_3 => Num3,
-            Keysym::_4 => Num4,
-            Keysym::_5 => Num5,
-            Keysym::_6 => Num6,
-            Keysym::_7 => Num7,
-            Keysym::_8 => Num8,
-            Keysym::_9 => Num9,
-            Keysym::a => A,
-            Keysym::b => B,
-            Keysym::c => C,
-            Keysym::d => D,
-            Keysym::e => E,
-            Keysym::f => F,
-            Keysym::g => G,
-            Keysym::h => H,
-            Keysym::i => I,
-            Keysym::j => J,
-            Keysym::k => K,
-            Keysym::l => L,
-            Keysym::m => M,
-            Keysym::n => N,
-            Keysym::o => O,
-            Keysym::p => P,
-            Keysym::q => Q,
-            Keysym::r => R,
-            Keysym::s => S,
-            Keysym::t => T,
-            Keysym::u => U,
-            Keysym::v => V,
-            Keysym::w => W,
-            Keysym::x => X,
-            Keysym::y => Y,
-            Keysym::z => Z,
+            Keysym::Return | Keysym::KP_Enter => Enter,
+            Keysym::space | Keysym::KP_Space => Space,
+            Keysym::Insert | Keysym::KP_Insert => Insert,
+            Keysym::Delete | Keysym::KP_Delete => Delete,
+            Keysym::Home | Keysym::KP_Home => Home,
+            Keysym::End | Keysym::KP_End => End,
+            Keysym::Page_Up | Keysym::KP_Page_Up => PageUp,
+            Keysym::Page_Down | Keysym::KP_Page_Down => PageDown,
+            Keysym::XF86_Copy | Keysym::SUN_Copy => Copy,
+            Keysym::XF86_Cut | Keysym::SUN_Cut => Cut,
+            Keysym::XF86_Paste | Keysym::SUN_Paste => Paste,
+            // Punctuation, shifted variants are normalized to their key
+            Keysym::colon => Colon,
+            Keysym::comma | Keysym::less | Keysym::KP_Separator => Comma,
+            Keysym::backslash => Backslash,
+            Keysym::slash | Keysym::KP_Divide => Slash,
+            Keysym::bar => Pipe,
+            Keysym::question => Questionmark,
+            Keysym::bracketleft | Keysym::braceleft => OpenBracket,
+            Keysym::bracketright | Keysym::braceright => CloseBracket,
+            Keysym::grave | Keysym::asciitilde => Backtick,
+            Keysym::minus | Keysym::underscore | Keysym::KP_Subtract => Minus,
+            Keysym::period | Keysym::greater | Keysym::KP_Decimal => Period,
+            Keysym::plus | Keysym::KP_Add => Plus,
+            Keysym::equal | Keysym::KP_Equal => Equals,
+            Keysym::semicolon => Semicolon,
+            Keysym::apostrophe | Keysym::quotedbl => Quote,
+            // Digits
+            Keysym::_0 | Keysym::KP_0 => Num0,
+            Keysym::_1 | Keysym::KP_1 => Num1,
+            Keysym::_2 | Keysym::KP_2 => Num2,
+            Keysym::_3 | Keysym::KP_3 => Num3,
+            Keysym::_4 | Keysym::KP_4 => Num4,
+            Keysym::_5 | Keysym::KP_5 => Num5,
+            Keysym::_6 | Keysym::KP_6 => Num6,
+            Keysym::_7 | Keysym::KP_7 => Num7,
+            Keysym::_8 | Keysym::KP_8 => Num8,
+            Keysym::_9 | Keysym::KP_9 => Num9,
+            // Letters
+            Keysym::a | Keysym::A => A,
+            Keysym::b | Keysym::B => B,
+            Keysym::c | Keysym::C => C,
+            Keysym::d | Keysym::D => D,
+            Keysym::e | Keysym::E => E,
+            Keysym::f | Keysym::F => F,
+            Keysym::g | Keysym::G => G,
+            Keysym::h | Keysym::H => H,
+            Keysym::i | Keysym::I => I,
+            Keysym::j | Keysym::J => J,
+            Keysym::k | Keysym::K => K,
+            Keysym::l | Keysym::L => L,
+            Keysym::m | Keysym::M => M,
+            Keysym::n | Keysym::N => N,
+            Keysym::o | Keysym::O => O,
+            Keysym::p | Keysym::P => P,
+            Keysym::q | Keysym::Q => Q,
+            Keysym::r | Keysym::R => R,
+            Keysym::s | Keysym::S => S,
+            Keysym::t | Keysym::T => T,
+            Keysym::u | Keysym::U => U,
+            Keysym::v | Keysym::V => V,
+            Keysym::w | Keysym::W => W,
+            Keysym::x | Keysym::X => X,
+            Keysym::y | Keysym::Y => Y,
+            Keysym::z | Keysym::Z => Z,
+            // Function keys
+            Keysym::F1 | Keysym::KP_F1 => F1,
+            Keysym::F2 | Keysym::KP_F2 => F2,
+            Keysym::F3 | Keysym::KP_F3 => F3,
+            Keysym::F4 | Keysym::KP_F4 => F4,
+            Keysym::F5 => F5,
+            Keysym::F6 => F6,
+            Keysym::F7 => F7,
+            Keysym::F8 => F8,
+            Keysym::F9 => F9,
+            Keysym::F10 => F10,
+            Keysym::F11 => F11,
+            Keysym::F12 => F12,
+            Keysym::F13 => F13,
+            Keysym::F14 => F14,
+            Keysym::F15 => F15,
+            Keysym::F16 => F16,
+            Keysym::F17 => F17,
+            Keysym::F18 => F18,
+            Keysym::F19 => F19,
+            Keysym::F20 => F20,
+            Keysym::F21 => F21,
+            Keysym::F22 => F22,
+            Keysym::F23 => F23,
+            Keysym::F24 => F24,
+            Keysym::F25 => F25,
+            Keysym::F26 => F26,
+            Keysym::F27 => F27,
+            Keysym::F28 => F28,
+            Keysym::F29 => F29,
+            Keysym::F30 => F30,
+            Keysym::F31 => F31,
+            Keysym::F32 => F32,
+            Keysym::F33 => F33,
+            Keysym::F34 => F34,
+            Keysym::F35 => F35,
             _ => {
                 return Err(());
             }
@@ -171,6 +491,29 @@ pub fn convert_button(button: MouseButton) -> Option<PointerButton> {
 
 pub struct ButtonWrapper(pub MouseButton);
 
+/// Convert from an evdev button code to smithay's [`MouseButton`], if possible
+pub fn convert_button_code(code: u32) -> Option<MouseButton> {
+    // evdev codes as defined in linux/input-event-codes.h
+    Some(match code {
+        0x110 => MouseButton::Left,    // BTN_LEFT
+        0x111 => MouseButton::Right,   // BTN_RIGHT
+        0x112 => MouseButton::Middle,  // BTN_MIDDLE
+        0x113 => MouseButton::Back,    // BTN_SIDE
+        0x114 => MouseButton::Forward, // BTN_EXTRA
+        0x115 => MouseButton::Forward, // BTN_FORWARD
+        0x116 => MouseButton::Back,    // BTN_BACK
+        0x14a => MouseButton::Left,    // BTN_TOUCH
+        0x14b => MouseButton::Right,   // BTN_STYLUS
+        0x14c => MouseButton::Middle,  // BTN_STYLUS2
+        _ => return None,
+    })
+}
+
+/// Convert from an evdev button code to egui's [`PointerButton`], if possible
+pub fn convert_pointer_button_code(code: u32) -> Option<PointerButton> {
+    convert_button_code(code).and_then(convert_button)
+}
+
 impl TryFrom<ButtonWrapper> for PointerButton {
     type Error = ();
 
@@ -179,9 +522,212 @@ impl TryFrom<ButtonWrapper> for PointerButton {
             MouseButton::Left => PointerButton::Primary,
             MouseButton::Middle => PointerButton::Middle,
             MouseButton::Right => PointerButton::Secondary,
+            MouseButton::Back => PointerButton::Extra1,
+            MouseButton::Forward => PointerButton::Extra2,
             _ => {
                 return Err(());
             }
         })
     }
 }
+
+/// Convert from egui's [`egui::CursorIcon`] to smithay's [`CursorImageStatus`]
+pub fn convert_cursor_icon(icon: EguiCursorIcon) -> CursorImageStatus {
+    CursorImageStatus::Named(match icon {
+        EguiCursorIcon::None => return CursorImageStatus::Hidden,
+        EguiCursorIcon::Default => CursorIcon::Default,
+        EguiCursorIcon::ContextMenu => CursorIcon::ContextMenu,
+        EguiCursorIcon::Help => CursorIcon::Help,
+        EguiCursorIcon::PointingHand => CursorIcon::Pointer,
+        EguiCursorIcon::Progress => CursorIcon::Progress,
+        EguiCursorIcon::Wait => CursorIcon::Wait,
+        EguiCursorIcon::Cell => CursorIcon::Cell,
+        EguiCursorIcon::Crosshair => CursorIcon::Crosshair,
+        EguiCursorIcon::Text => CursorIcon::Text,
+        EguiCursorIcon::VerticalText => CursorIcon::VerticalText,
+        EguiCursorIcon::Alias => CursorIcon::Alias,
+        EguiCursorIcon::Copy => CursorIcon::Copy,
+        EguiCursorIcon::Move => CursorIcon::Move,
+        EguiCursorIcon::NoDrop => CursorIcon::NoDrop,
+        EguiCursorIcon::NotAllowed => CursorIcon::NotAllowed,
+        EguiCursorIcon::Grab => CursorIcon::Grab,
+        EguiCursorIcon::Grabbing => CursorIcon::Grabbing,
+        EguiCursorIcon::AllScroll => CursorIcon::AllScroll,
+        EguiCursorIcon::ResizeHorizontal => CursorIcon::EwResize,
+        EguiCursorIcon::ResizeNeSw => CursorIcon::NeswResize,
+        EguiCursorIcon::ResizeNwSe => CursorIcon::NwseResize,
+        EguiCursorIcon::ResizeVertical => CursorIcon::NsResize,
+        EguiCursorIcon::ResizeEast => CursorIcon::EResize,
+        EguiCursorIcon::ResizeSouthEast => CursorIcon::SeResize,
+        EguiCursorIcon::ResizeSouth => CursorIcon::SResize,
+        EguiCursorIcon::ResizeSouthWest => CursorIcon::SwResize,
+        EguiCursorIcon::ResizeWest => CursorIcon::WResize,
+        EguiCursorIcon::ResizeNorthWest => CursorIcon::NwResize,
+        EguiCursorIcon::ResizeNorth => CursorIcon::NResize,
+        EguiCursorIcon::ResizeNorthEast => CursorIcon::NeResize,
+        EguiCursorIcon::ResizeColumn => CursorIcon::ColResize,
+        EguiCursorIcon::ResizeRow => CursorIcon::RowResize,
+        EguiCursorIcon::ZoomIn => CursorIcon::ZoomIn,
+        EguiCursorIcon::ZoomOut => CursorIcon::ZoomOut,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(sym: Keysym) -> Option<Key> {
+        KeysymConv(sym).try_into().ok()
+    }
+
+    #[test]
+    fn every_key_is_reachable() {
+        let extra = [Keysym::XF86_Copy, Keysym::XF86_Cut, Keysym::XF86_Paste];
+        let mapped = (0..=0xffff)
+            .map(Keysym::new)
+            .chain(extra)
+            .filter_map(key)
+            .collect::<std::collections::HashSet<_>>();
+
+        for k in Key::ALL {
+            assert!(mapped.contains(k), "{:?} has no keysym mapped to it", k);
+        }
+        // egui has no equivalent of this key
+        assert_eq!(key(Keysym::Help), None);
+    }
+
+    #[test]
+    fn non_latin_keys_fall_back_to_physical() {
+        // the keys of W and S on a russian layout
+        let tse = convert_key_event([Keysym::Cyrillic_tse].into_iter(), Keycode::new(17 + 8));
+        assert_eq!(tse, Some((Key::W, Some(Key::W))));
+        let yeru = convert_key_event([Keysym::Cyrillic_yeru].into_iter(), Keycode::new(31 + 8));
+        assert_eq!(yeru, Some((Key::S, Some(Key::S))));
+
+        // the logical key still follows the layout, e.g. W on an AZERTY Z key
+        let w = convert_key_event([Keysym::w].into_iter(), Keycode::new(44 + 8));
+        assert_eq!(w, Some((Key::W, Some(Key::Z))));
+        assert_eq!(
+            convert_key_event([Keysym::Cyrillic_tse].into_iter(), Keycode::new(8)),
+            None
+        );
+    }
+
+    #[test]
+    fn control_characters_are_not_printable() {
+        for text in ["\r", "\t", "\x08", "\x1b", "\r\n"] {
+            assert_eq!(printable(text.to_string()), None, "{:?}", text);
+        }
+        assert_eq!(printable("a\tb".to_string()), Some("ab".to_string()));
+        assert_eq!(printable("\u{e9}".to_string()), Some("\u{e9}".to_string()));
+    }
+
+    #[test]
+    fn button_codes() {
+        assert_eq!(
+            convert_pointer_button_code(0x110),
+            Some(PointerButton::Primary)
+        );
+        assert_eq!(
+            convert_pointer_button_code(0x111),
+            Some(PointerButton::Secondary)
+        );
+        assert_eq!(
+            convert_pointer_button_code(0x112),
+            Some(PointerButton::Middle)
+        );
+        for back in [0x113, 0x116] {
+            assert_eq!(
+                convert_pointer_button_code(back),
+                Some(PointerButton::Extra1)
+            );
+        }
+        for forward in [0x114, 0x115] {
+            assert_eq!(
+                convert_pointer_button_code(forward),
+                Some(PointerButton::Extra2)
+            );
+        }
+        assert_eq!(convert_pointer_button_code(0x117), None); // BTN_TASK
+        assert_eq!(convert_pointer_button_code(0x1e), None); // KEY_A
+    }
+
+    #[test]
+    fn function_keys() {
+        let keys = (0..35)
+            .map(|i| key(Keysym::new(Keysym::F1.raw() + i)).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(keys.first(), Some(&Key::F1));
+        assert_eq!(keys.get(11), Some(&Key::F12));
+        assert_eq!(keys.last(), Some(&Key::F35));
+        assert_eq!(key(Keysym::KP_F4), Some(Key::F4));
+    }
+
+    #[test]
+    fn letters_ignore_case() {
+        for (lower, upper) in (Keysym::a.raw()..=Keysym::z.raw()).zip(Keysym::A.raw()..) {
+            let lower = key(Keysym::new(lower));
+            assert!(lower.is_some());
+            assert_eq!(lower, key(Keysym::new(upper)));
+        }
+    }
+
+    #[test]
+    fn keypad_is_normalized() {
+        for i in 0..10 {
+            assert_eq!(
+                key(Keysym::new(Keysym::KP_0.raw() + i)),
+                key(Keysym::new(Keysym::_0.raw() + i)),
+            );
+        }
+        assert_eq!(key(Keysym::KP_Enter), Some(Key::Enter));
+        assert_eq!(key(Keysym::KP_Add), Some(Key::Plus));
+        assert_eq!(key(Keysym::KP_Subtract), Some(Key::Minus));
+        assert_eq!(key(Keysym::KP_Divide), Some(Key::Slash));
+        assert_eq!(key(Keysym::KP_Decimal), Some(Key::Period));
+        assert_eq!(key(Keysym::KP_Page_Up), Some(Key::PageUp));
+        assert_eq!(key(Keysym::KP_Left), Some(Key::ArrowLeft));
+    }
+
+    #[test]
+    fn shifted_punctuation_is_normalized() {
+        assert_eq!(key(Keysym::underscore), Some(Key::Minus));
+        assert_eq!(key(Keysym::braceleft), Some(Key::OpenBracket));
+        assert_eq!(key(Keysym::braceright), Some(Key::CloseBracket));
+        assert_eq!(key(Keysym::asciitilde), Some(Key::Backtick));
+        assert_eq!(key(Keysym::quotedbl), Some(Key::Quote));
+        assert_eq!(key(Keysym::less), Some(Key::Comma));
+        assert_eq!(key(Keysym::greater), Some(Key::Period));
+        assert_eq!(key(Keysym::ISO_Left_Tab), Some(Key::Tab));
+    }
+
+    #[test]
+    fn unmapped_keysyms() {
+        assert_eq!(key(Keysym::Shift_L), None);
+        assert_eq!(key(Keysym::Control_R), None);
+        assert_eq!(key(Keysym::dead_acute), None);
+        assert_eq!(key(Keysym::NoSymbol), None);
+    }
+
+    #[test]
+    fn physical_keys() {
+        let physical = |code: u32| convert_physical_key(Keycode::new(code + 8));
+        assert_eq!(physical(17), Some(Key::W)); // KEY_W
+        assert_eq!(physical(30), Some(Key::A)); // KEY_A
+        assert_eq!(physical(31), Some(Key::S)); // KEY_S
+        assert_eq!(physical(32), Some(Key::D)); // KEY_D
+        assert_eq!(physical(88), Some(Key::F12)); // KEY_F12
+        assert_eq!(physical(79), Some(Key::Num1)); // KEY_KP1
+        assert_eq!(physical(42), None); // KEY_LEFTSHIFT
+        assert_eq!(convert_physical_key(Keycode::new(0)), None);
+    }
+
+    #[test]
+    fn convert_key_uses_first_known_keysym() {
+        assert_eq!(
+            convert_key([Keysym::Shift_L, Keysym::P, Keysym::F12].into_iter()),
+            Some(Key::P)
+        );
+        assert_eq!(convert_key(std::iter::empty()), None);
+    }
+}
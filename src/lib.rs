@@ -1,13 +1,18 @@
 use egui::PlatformOutput;
 #[deny(missing_docs)]
-use egui::{Context, Event, FullOutput, Pos2, RawInput, Rect, Vec2};
+use egui::{
+    Context, Event, FullOutput, Pos2, RawInput, Rect, TouchDeviceId, TouchId, TouchPhase, Vec2,
+    ViewportId, ViewportInfo,
+};
 use egui_glow::Painter;
 #[cfg(feature = "desktop_integration")]
 use smithay::desktop::space::SpaceElement;
 use smithay::{
     backend::{
         allocator::Fourcc,
-        input::{ButtonState, Device, DeviceCapability, KeyState, MouseButton},
+        input::{
+            AxisSource, ButtonState, Device, DeviceCapability, KeyState, MouseButton, TouchSlot,
+        },
         renderer::{
             element::{
                 texture::{TextureRenderBuffer, TextureRenderElement},
@@ -20,16 +25,35 @@ use smithay::{
     },
     desktop::space::RenderZindex,
     input::{
-        keyboard::{KeyboardTarget, KeysymHandle, ModifiersState},
+        keyboard::{
+            Error as KeyboardError, GrabStartData as KeyboardGrabStartData, KeyboardHandle,
+            KeyboardTarget, KeysymHandle, ModifiersState, XkbConfig,
+        },
         pointer::{
-            AxisFrame, ButtonEvent, GestureHoldBeginEvent, GestureHoldEndEvent,
-            GesturePinchBeginEvent, GesturePinchEndEvent, GesturePinchUpdateEvent,
-            GestureSwipeBeginEvent, GestureSwipeEndEvent, GestureSwipeUpdateEvent, MotionEvent,
-            PointerTarget, RelativeMotionEvent,
+            AxisFrame, ButtonEvent, CursorImageStatus, Focus, GestureHoldBeginEvent,
+            GestureHoldEndEvent, GesturePinchBeginEvent, GesturePinchEndEvent,
+            GesturePinchUpdateEvent, GestureSwipeBeginEvent, GestureSwipeEndEvent,
+            GestureSwipeUpdateEvent, MotionEvent, PointerHandle, PointerTarget,
+            RelativeMotionEvent,
+        },
+        touch::{
+            DownEvent, MotionEvent as TouchMotionEvent, OrientationEvent, ShapeEvent, TouchTarget,
+            UpEvent,
         },
         Seat, SeatHandler,
     },
-    utils::{IsAlive, Logical, Physical, Point, Rectangle, Serial, Size, Transform},
+    reexports::wayland_server::DisplayHandle,
+    utils::{Buffer, IsAlive, Logical, Physical, Point, Rectangle, Serial, Size, Transform},
+    wayland::selection::{
+        data_device::{
+            request_data_device_client_selection, set_data_device_selection, DataDeviceHandler,
+            SelectionRequestError,
+        },
+        primary_selection::{
+            request_primary_client_selection, set_primary_selection, PrimarySelectionHandler,
+        },
+        SelectionHandler, SelectionTarget,
+    },
 };
 use xkbcommon::xkb::Keycode;
 
@@ -37,13 +61,32 @@ use std::{
     cell::RefCell,
     collections::HashMap,
     fmt,
+    fs::File,
+    io,
+    os::fd::OwnedFd,
+    path::Path,
     rc::Rc,
     sync::{Arc, Mutex},
-    time::Instant,
+    time::{Duration, Instant},
 };
 
+mod clipboard;
+#[cfg(feature = "cursor_theme")]
+mod cursor;
+mod damage;
+mod grabs;
 mod input;
-pub use self::input::{convert_button, convert_key, convert_modifiers};
+#[cfg(feature = "calloop")]
+mod repaint;
+#[cfg(feature = "cursor_theme")]
+pub use self::cursor::{cursor_shape_name, CursorBuffer, XCursorTheme};
+pub use self::grabs::{EguiKeyboardGrab, EguiPointerGrab};
+pub use self::input::{
+    convert_button, convert_button_code, convert_cursor_icon, convert_key, convert_key_event,
+    convert_modifiers, convert_physical_key, convert_pointer_button_code,
+};
+#[cfg(feature = "calloop")]
+pub use self::repaint::RepaintTimer;
 
 /// smithay-egui state object
 #[derive(Debug, Clone)]
@@ -51,6 +94,9 @@ pub struct EguiState {
     inner: Arc<Mutex<EguiInner>>,
     ctx: Context,
     start_time: Instant,
+    // when egui wants to be painted next, `None` if never.
+    // Separate from `inner` as egui may request repaints while `inner` is locked.
+    next_repaint: Arc<Mutex<Option<Instant>>>,
 }
 
 impl PartialEq for EguiState {
@@ -60,14 +106,37 @@ impl PartialEq for EguiState {
 }
 
 struct EguiInner {
-    pointers: usize,
-    last_pointer_position: Point<i32, Logical>,
+    devices: HashMap<String, DeviceCapabilities>,
+    last_pointer_position: Point<f64, Logical>,
+    pressed_buttons: Vec<egui::PointerButton>,
+    // if an `EguiPointerGrab` is active
+    pointer_grab: bool,
+    // if an `EguiKeyboardGrab` is active
+    keyboard_grab: bool,
+    touches: Vec<TouchPoint>,
+    pointer_touch: Option<(TouchDeviceId, TouchSlot)>,
+    pending_axis: Option<(egui::MouseWheelUnit, Vec2)>,
+    gesture_policy: GesturePolicy,
+    gesture: Option<Gesture>,
     area: Rectangle<i32, Logical>,
+    // render buffer used for the last frame
+    buffer_spec: Option<BufferSpec>,
     last_modifiers: ModifiersState,
     last_output: Option<PlatformOutput>,
     pressed: Vec<(Option<egui::Key>, Keycode)>,
+    repeat_delay: i32,
+    repeat_rate: i32,
+    key_repeat: Option<KeyRepeat>,
     focused: bool,
     events: Vec<Event>,
+    selections: clipboard::Selections,
+    hovering_text: bool,
+    cursor_icon: egui::CursorIcon,
+    // cursor icon last set through `SeatHandler::cursor_image`
+    applied_cursor_icon: Option<egui::CursorIcon>,
+    // primitives painted in the last frame
+    last_primitives: Vec<damage::PrimitiveDigest>,
+    url_handler: Option<Box<dyn UrlHandler>>,
     kbd: Option<input::KbdInternal>,
     #[cfg(feature = "desktop_integration")]
     z_index: u8,
@@ -76,14 +145,32 @@ struct EguiInner {
 impl fmt::Debug for EguiInner {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut d = f.debug_struct("EguiInner");
-        d.field("pointers", &self.pointers)
+        d.field("devices", &self.devices)
             .field("last_pointer_position", &self.last_pointer_position)
+            .field("pressed_buttons", &self.pressed_buttons)
+            .field("pointer_grab", &self.pointer_grab)
+            .field("keyboard_grab", &self.keyboard_grab)
+            .field("touches", &self.touches)
+            .field("pointer_touch", &self.pointer_touch)
+            .field("pending_axis", &self.pending_axis)
+            .field("gesture_policy", &self.gesture_policy)
+            .field("gesture", &self.gesture)
             .field("area", &self.area)
+            .field("buffer_spec", &self.buffer_spec)
             .field("last_modifiers", &self.last_modifiers)
             .field("last_output", &self.last_output.as_ref().map(|_| "..."))
             .field("pressed", &self.pressed)
+            .field("repeat_delay", &self.repeat_delay)
+            .field("repeat_rate", &self.repeat_rate)
+            .field("key_repeat", &self.key_repeat)
             .field("focused", &self.focused)
             .field("events", &self.events)
+            .field("selections", &self.selections)
+            .field("hovering_text", &self.hovering_text)
+            .field("cursor_icon", &self.cursor_icon)
+            .field("applied_cursor_icon", &self.applied_cursor_icon)
+            .field("last_primitives", &self.last_primitives.len())
+            .field("url_handler", &self.url_handler.as_ref().map(|_| "..."))
             .field("kbd", &self.kbd);
 
         #[cfg(feature = "desktop_integration")]
@@ -95,6 +182,123 @@ impl fmt::Debug for EguiInner {
     }
 }
 
+// input capabilities of a device relevant to egui's pointer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct DeviceCapabilities {
+    pointer: bool,
+    touch: bool,
+    tablet: bool,
+}
+
+impl DeviceCapabilities {
+    fn of(device: &impl Device) -> Self {
+        DeviceCapabilities {
+            pointer: device.has_capability(DeviceCapability::Pointer),
+            touch: device.has_capability(DeviceCapability::Touch),
+            tablet: device.has_capability(DeviceCapability::TabletTool),
+        }
+    }
+
+    fn is_pointing(&self) -> bool {
+        self.pointer || self.touch || self.tablet
+    }
+}
+
+/// Policy deciding which touchpad gestures are consumed by egui
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GesturePolicy {
+    /// Translate pinch gestures into [`egui::Event::Zoom`]
+    pub pinch_zoom: bool,
+    /// Emulate a two-finger touch for pinch gestures,
+    /// so that [`egui::InputState::multi_touch`] also reports rotation.
+    pub pinch_rotation: bool,
+    /// Translate swipe gestures into smooth scrolling
+    pub swipe_scroll: bool,
+    /// Swipes with more fingers than this are ignored, e.g. to leave them to workspace switching
+    pub swipe_max_fingers: u32,
+}
+
+impl Default for GesturePolicy {
+    fn default() -> Self {
+        GesturePolicy {
+            pinch_zoom: true,
+            pinch_rotation: false,
+            swipe_scroll: true,
+            swipe_max_fingers: 3,
+        }
+    }
+}
+
+/// When an [`EguiState`] needs to be rendered next, see [`EguiState::next_repaint`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepaintDeadline {
+    /// Input is pending or a repaint is overdue
+    Immediately,
+    /// egui wants to be rendered at the given point in time, e.g. to continue an animation or repeat a key
+    At(Instant),
+    /// Nothing changes until new input arrives
+    Never,
+}
+
+/// Handler for urls egui requests to open, e.g. by clicking an [`egui::Hyperlink`]
+pub trait UrlHandler: Send {
+    /// Open `open_url.url`, in a new tab if `open_url.new_tab` is set
+    fn open_url(&mut self, open_url: &egui::OpenUrl);
+}
+
+impl<F: FnMut(&egui::OpenUrl) + Send> UrlHandler for F {
+    fn open_url(&mut self, open_url: &egui::OpenUrl) {
+        self(open_url)
+    }
+}
+
+/// Default [`UrlHandler`] spawning `xdg-open` in the environment of the compositor
+#[derive(Debug, Clone, Copy, Default)]
+pub struct XdgOpen;
+
+impl UrlHandler for XdgOpen {
+    fn open_url(&mut self, open_url: &egui::OpenUrl) {
+        match std::process::Command::new("xdg-open")
+            .arg(&open_url.url)
+            .spawn()
+        {
+            // reap the child to not leave a zombie behind
+            Ok(mut child) => {
+                std::thread::spawn(move || child.wait());
+            }
+            Err(err) => log::warn!("Failed to open {}: {}", open_url.url, err),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Gesture {
+    Swipe,
+    Pinch { scale: f64, rotation: f64 },
+}
+
+// virtual touch device used to emulate pinch gestures
+const PINCH_TOUCH_DEVICE: TouchDeviceId = TouchDeviceId(u64::MAX);
+const PINCH_TOUCH_RADIUS: f64 = 100.0;
+
+#[derive(Debug, Clone)]
+struct KeyRepeat {
+    key: Option<egui::Key>,
+    code: Keycode,
+    text: Option<String>,
+    // timestamp of the next repeat
+    next: u32,
+    // `next` on the monotonic clock, to schedule repaints
+    deadline: Instant,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TouchPoint {
+    device: TouchDeviceId,
+    slot: TouchSlot,
+    position: Point<f64, Logical>,
+}
+
 struct GlState {
     painter: Painter,
     render_buffers: HashMap<usize, TextureRenderBuffer<GlesTexture>>,
@@ -106,19 +310,48 @@ type UserDataType = Rc<RefCell<GlState>>;
 impl EguiState {
     /// Creates a new `EguiState`
     pub fn new(area: Rectangle<i32, Logical>) -> EguiState {
+        let ctx = Context::default();
+        // frames are painted at exactly the output scale, which zooming would break
+        ctx.options_mut(|options| options.zoom_with_keyboard = false);
+        // the first frame is always painted
+        let next_repaint = Arc::new(Mutex::new(Some(Instant::now())));
+        let requested = next_repaint.clone();
+        ctx.set_request_repaint_callback(move |info| {
+            request_repaint(&requested, info.delay);
+        });
+
         EguiState {
-            ctx: Context::default(),
+            ctx,
             start_time: Instant::now(),
+            next_repaint,
             inner: Arc::new(Mutex::new(EguiInner {
-                pointers: 0,
-                last_pointer_position: (0, 0).into(),
+                devices: HashMap::new(),
+                last_pointer_position: (0.0, 0.0).into(),
+                pressed_buttons: Vec::new(),
+                pointer_grab: false,
+                keyboard_grab: false,
+                touches: Vec::new(),
+                pointer_touch: None,
+                pending_axis: None,
+                gesture_policy: GesturePolicy::default(),
+                gesture: None,
                 area,
+                buffer_spec: None,
                 last_modifiers: ModifiersState::default(),
                 last_output: None,
                 events: Vec::new(),
+                selections: clipboard::Selections::default(),
+                hovering_text: false,
+                cursor_icon: egui::CursorIcon::Default,
+                applied_cursor_icon: None,
+                last_primitives: Vec::new(),
                 focused: false,
                 pressed: Vec::new(),
-                kbd: match input::KbdInternal::new() {
+                repeat_delay: 200,
+                repeat_rate: 25,
+                key_repeat: None,
+                url_handler: Some(Box::new(XdgOpen)),
+                kbd: match input::KbdInternal::new(&XkbConfig::default()) {
                     Some(kbd) => Some(kbd),
                     None => {
                         log::error!("Failed to initialize keymap for text input in egui.");
@@ -153,24 +386,72 @@ impl EguiState {
         self.ctx.wants_pointer_input()
     }
 
+    /// The cursor image egui requests, if egui is currently interested in the pointer.
+    ///
+    /// This reflects the last [`EguiState::render`] call.
+    /// When egui receives pointer events through the [`PointerTarget`] implementation,
+    /// the image is applied automatically using [`SeatHandler::cursor_image`].
+    pub fn cursor_image(&self) -> Option<CursorImageStatus> {
+        let icon = self.inner.lock().unwrap().cursor_icon;
+        self.wants_pointer().then(|| convert_cursor_icon(icon))
+    }
+
     /// Pass new input devices to `EguiState` for internal tracking
     pub fn handle_device_added(&self, device: &impl Device) {
-        if device.has_capability(DeviceCapability::Pointer) {
-            self.inner.lock().unwrap().pointers += 1;
+        let capabilities = DeviceCapabilities::of(device);
+        if capabilities.is_pointing() {
+            self.inner
+                .lock()
+                .unwrap()
+                .devices
+                .insert(device.id(), capabilities);
         }
     }
 
     /// Remove input devices to `EguiState` for internal tracking
+    ///
+    /// Once the last device able to move egui's pointer is gone, held buttons are released
+    /// and egui is notified with [`Event::PointerGone`].
+    /// Devices that were never added are ignored.
     pub fn handle_device_removed(&self, device: &impl Device) {
         let mut inner = self.inner.lock().unwrap();
-        if device.has_capability(DeviceCapability::Pointer) {
-            inner.pointers -= 1;
-        }
-        if inner.pointers == 0 {
+        if inner.devices.remove(&device.id()).is_some() && inner.devices.is_empty() {
+            inner.release_buttons();
             inner.events.push(Event::PointerGone);
         }
     }
 
+    /// Use the keymap described by `config` for text input.
+    ///
+    /// This should match the configuration of the seat's keyboard as passed to
+    /// [`Seat::add_keyboard`] or [`smithay::input::keyboard::KeyboardHandle::set_xkb_config`].
+    /// The default is the keymap of an empty [`XkbConfig`].
+    pub fn set_xkb_config(&self, config: XkbConfig<'_>) -> Result<(), KeyboardError> {
+        let kbd = input::KbdInternal::new(&config).ok_or(KeyboardError::BadKeymap)?;
+        self.inner.lock().unwrap().replace_kbd(kbd);
+        Ok(())
+    }
+
+    /// Use a keymap in the xkb text format for text input.
+    ///
+    /// Use this instead of [`EguiState::set_xkb_config`], if your compositor loads its keymap from a file.
+    pub fn set_keymap(&self, keymap: String) -> Result<(), KeyboardError> {
+        let kbd = input::KbdInternal::from_string(keymap).ok_or(KeyboardError::BadKeymap)?;
+        self.inner.lock().unwrap().replace_kbd(kbd);
+        Ok(())
+    }
+
+    /// Load the compose table used for dead keys and compose sequences from `path`.
+    ///
+    /// By default the system table for the current locale is used.
+    pub fn set_compose_file(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let table = input::compose_table_from_file(path.as_ref())?;
+        if let Some(kbd) = self.inner.lock().unwrap().kbd.as_mut() {
+            kbd.set_compose_table(Some(&table));
+        }
+        Ok(())
+    }
+
     /// Pass keyboard events into `EguiState`.
     ///
     /// You do not want to pass in events, egui should not react to, but you need to make sure they add up.
@@ -178,13 +459,22 @@ impl EguiState {
     ///
     /// You likely want to use the filter-closure of [`smithay::wayland::seat::KeyboardHandle::input`] to optain these values.
     /// Use [`smithay::wayland::seat::KeysymHandle`] and the provided [`smithay::wayland::seat::ModifiersState`].
-    pub fn handle_keyboard(&self, handle: &KeysymHandle, pressed: bool, modifiers: ModifiersState) {
+    /// `time` is the timestamp of the event in milliseconds and is used for key repeat, see [`EguiState::handle_key_repeat`].
+    pub fn handle_keyboard(
+        &self,
+        handle: &KeysymHandle,
+        pressed: bool,
+        modifiers: ModifiersState,
+        time: u32,
+    ) {
         let mut inner = self.inner.lock().unwrap();
-        inner.last_modifiers = modifiers;
-        let key = if let Some(key) = convert_key(handle.raw_syms().iter().copied()) {
+        let modifiers_changed = inner.update_modifiers(modifiers);
+        let key = if let Some((key, physical_key)) =
+            convert_key_event(handle.raw_syms().iter().copied(), handle.raw_code())
+        {
             inner.events.push(Event::Key {
                 key,
-                physical_key: None,
+                physical_key,
                 pressed,
                 repeat: false,
                 modifiers: convert_modifiers(modifiers),
@@ -194,33 +484,123 @@ impl EguiState {
             None
         };
 
+        if let (Some(key), true) = (key, pressed) {
+            let modifiers = convert_modifiers(modifiers);
+            if clipboard::is_cut_command(modifiers, key) {
+                inner.events.push(Event::Cut);
+            } else if clipboard::is_copy_command(modifiers, key) {
+                inner.events.push(Event::Copy);
+            } else if clipboard::is_paste_command(modifiers, key) {
+                // resolved by `EguiState::update_selection`
+                inner.selections.clipboard.paste_requested = true;
+            }
+        }
+
         if pressed {
             inner.pressed.push((key, handle.raw_code()));
         } else {
             inner.pressed.retain(|(_, code)| code != &handle.raw_code());
         }
 
-        if let Some(kbd) = inner.kbd.as_mut() {
-            kbd.key_input(handle.raw_code().raw(), pressed);
+        let text = inner.key_input(
+            handle.raw_code(),
+            pressed,
+            modifiers_changed.then_some(&modifiers),
+        );
 
-            if pressed {
-                let utf8 = kbd.get_utf8(handle.raw_code().raw());
-                /* utf8 contains the utf8 string generated by that keystroke
-                 * it can contain 1, multiple characters, or even be empty
-                 */
-                inner.events.push(Event::Text(utf8));
+        if pressed {
+            let repeats = inner
+                .kbd
+                .as_ref()
+                .is_none_or(|kbd| kbd.key_repeats(handle.raw_code().raw()));
+            if repeats {
+                inner.start_key_repeat(key, handle.raw_code(), text, time);
+            }
+        } else {
+            inner.stop_key_repeat(handle.raw_code());
+        }
+    }
+
+    /// Start an [`EguiKeyboardGrab`] on `keyboard`, routing all key events to egui.
+    ///
+    /// Use this while egui shows a modal dialog, that needs to capture the keyboard regardless of focus.
+    /// The grab is released by [`EguiState::stop_keyboard_grab`] or with the first key event
+    /// after egui stopped [wanting keyboard input](EguiState::wants_keyboard), if it wanted it before.
+    /// Dialogs without text input have to be closed with [`EguiState::stop_keyboard_grab`].
+    pub fn start_keyboard_grab<D>(&self, keyboard: &KeyboardHandle<D>, data: &mut D, serial: Serial)
+    where
+        D: SeatHandler + 'static,
+        D::KeyboardFocus: From<EguiState>,
+    {
+        {
+            let mut inner = self.inner.lock().unwrap();
+            if inner.keyboard_grab {
+                return;
             }
+            inner.keyboard_grab = true;
+        }
+
+        let start_data = KeyboardGrabStartData {
+            focus: keyboard.current_focus(),
+        };
+        keyboard.set_focus(data, Some(self.clone().into()), serial);
+        keyboard.set_grab(
+            data,
+            EguiKeyboardGrab::new(self.clone(), start_data),
+            serial,
+        );
+    }
+
+    /// Release a grab started by [`EguiState::start_keyboard_grab`] and restore the previous keyboard focus
+    pub fn stop_keyboard_grab<D>(&self, keyboard: &KeyboardHandle<D>, data: &mut D, serial: Serial)
+    where
+        D: SeatHandler + 'static,
+    {
+        if !self.inner.lock().unwrap().keyboard_grab {
+            return;
+        }
+        let focus = keyboard
+            .grab_start_data()
+            .and_then(|start_data| start_data.focus);
+        keyboard.unset_grab(data);
+        keyboard.set_focus(data, focus, serial);
+    }
+
+    /// Set the key repeat delay (in milliseconds) and rate (in repeats per second) used by `EguiState`.
+    ///
+    /// This should match the values passed to [`Seat::add_keyboard`].
+    /// A rate of zero disables key repeat. The default is a delay of 200ms and a rate of 25.
+    pub fn set_key_repeat(&self, delay: i32, rate: i32) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.repeat_delay = delay;
+        inner.repeat_rate = rate;
+        if rate <= 0 {
+            inner.key_repeat = None;
         }
     }
 
+    /// Generate repeated key and text events for a held key.
+    ///
+    /// `time` is the current time in milliseconds, using the same clock as the timestamps of the key events.
+    /// Call this regularly, e.g. before every [`EguiState::render`] call.
+    ///
+    /// At most one repeat is generated per call, so calling this late does not produce a burst of events.
+    ///
+    /// Returns the time of the next repeat, if a key is currently repeating.
+    pub fn handle_key_repeat(&self, time: u32) -> Option<u32> {
+        self.inner.lock().unwrap().key_repeat(time)
+    }
+
     /// Pass new pointer coordinates to `EguiState`
-    pub fn handle_pointer_motion(&self, position: Point<i32, Logical>) {
+    ///
+    /// `position` is expected in the same coordinate space as the `area` passed to [`EguiState::render`].
+    /// When routing input through a [`Seat`], use the location of `area` as the location of the focus.
+    /// That is the location a `Space` reports for an `EguiState` mapped at the location of its `area`.
+    pub fn handle_pointer_motion(&self, position: Point<f64, Logical>) {
         let mut inner = self.inner.lock().unwrap();
         inner.last_pointer_position = position;
-        inner.events.push(Event::PointerMoved(Pos2::new(
-            position.x as f32,
-            position.y as f32,
-        )))
+        let pos = inner.egui_pos(position);
+        inner.events.push(Event::PointerMoved(pos))
     }
 
     /// Pass pointer button presses to `EguiState`
@@ -231,10 +611,35 @@ impl EguiState {
     pub fn handle_pointer_button(&self, button: MouseButton, pressed: bool) {
         if let Some(button) = convert_button(button) {
             let mut inner = self.inner.lock().unwrap();
-            let last_pos = inner.last_pointer_position;
+            if pressed {
+                if !inner.pressed_buttons.contains(&button) {
+                    inner.pressed_buttons.push(button);
+                }
+            } else if let Some(idx) = inner.pressed_buttons.iter().position(|b| *b == button) {
+                inner.pressed_buttons.remove(idx);
+            } else {
+                // the button was pressed before the pointer entered egui
+                return;
+            }
+
+            let pos = inner.egui_pos(inner.last_pointer_position);
             let modifiers = convert_modifiers(inner.last_modifiers);
+
+            if button == egui::PointerButton::Middle && pressed && inner.hovering_text {
+                // move the text cursor to the pointer before pasting the primary selection
+                for pressed in [true, false] {
+                    inner.events.push(Event::PointerButton {
+                        pos,
+                        button: egui::PointerButton::Primary,
+                        pressed,
+                        modifiers,
+                    });
+                }
+                inner.selections.primary.paste_requested = true;
+            }
+
             inner.events.push(Event::PointerButton {
-                pos: Pos2::new(last_pos.x as f32, last_pos.y as f32),
+                pos,
                 button,
                 pressed,
                 modifiers,
@@ -242,6 +647,54 @@ impl EguiState {
         }
     }
 
+    /// Notify `EguiState` that the pointer left its area
+    ///
+    /// Buttons still held are released, so that no drag remains active.
+    pub fn handle_pointer_leave(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.release_buttons();
+        inner.events.push(Event::PointerGone);
+    }
+
+    /// Start an [`EguiPointerGrab`] on `pointer`, if egui is currently dragging a widget.
+    ///
+    /// Call this after [`EguiState::render`] while egui has the pointer focus.
+    /// Pointer events are routed to egui until all buttons are released,
+    /// even if the pointer leaves the area of egui.
+    ///
+    /// Returns true if a grab was started or is already active.
+    pub fn start_pointer_grab<D>(
+        &self,
+        pointer: &PointerHandle<D>,
+        data: &mut D,
+        serial: Serial,
+    ) -> bool
+    where
+        D: SeatHandler + 'static,
+    {
+        if !self.ctx.is_using_pointer() {
+            return false;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        if inner.pointer_grab {
+            return true;
+        }
+        // only drags started by a button press can be grabbed
+        let Some(start_data) = pointer.grab_start_data() else {
+            return false;
+        };
+        inner.pointer_grab = true;
+        std::mem::drop(inner);
+
+        pointer.set_grab(
+            data,
+            EguiPointerGrab::new(self.clone(), start_data),
+            serial,
+            Focus::Keep,
+        );
+        true
+    }
+
     /// Pass a pointer axis scrolling to `EguiState`
     ///
     /// Note: If you are unsure about *which* PointerAxisEvents to send to smithay-egui
@@ -249,6 +702,7 @@ impl EguiState {
     ///       if there is an egui-element below your pointer.
     pub fn handle_pointer_axis(&self, x_amount: f64, y_amount: f64) {
         let mut inner = self.inner.lock().unwrap();
+        inner.flush_axis();
         let modifiers = convert_modifiers(inner.last_modifiers);
         inner.events.push(Event::MouseWheel {
             unit: egui::MouseWheelUnit::Point,
@@ -260,22 +714,262 @@ impl EguiState {
         })
     }
 
+    /// Synchronize egui's clipboard with the `wl_data_device` selection of `seat`.
+    ///
+    /// Call this after every [`EguiState::render`] call.
+    /// Text copied in egui is published as a compositor-owned selection using the provided `user_data`
+    /// and paste requests of egui are answered with the current selection of a client.
+    ///
+    /// Your [`SelectionHandler::send_selection`] implementation needs to forward requests for
+    /// selections tagged with this `user_data` to [`EguiState::send_selection`].
+    pub fn update_selection<D>(
+        &self,
+        dh: &DisplayHandle,
+        seat: &Seat<D>,
+        user_data: <D as SelectionHandler>::SelectionUserData,
+    ) where
+        D: SeatHandler + DataDeviceHandler + 'static,
+    {
+        let mut inner = self.inner.lock().unwrap();
+        if std::mem::take(&mut inner.selections.clipboard.dirty) {
+            set_data_device_selection(dh, seat, clipboard::text_mime_types(), user_data);
+        }
+
+        if std::mem::take(&mut inner.selections.clipboard.paste_requested) {
+            let requested = clipboard::request_text(|mime_type, fd| {
+                request_data_device_client_selection(seat, mime_type, fd)
+            });
+            let fallback = inner.selections.clipboard.text.clone();
+            self.paste(&mut inner, requested, fallback);
+        }
+    }
+
+    /// Synchronize the text selected in egui with the primary selection of `seat`.
+    ///
+    /// Works like [`EguiState::update_selection`], but for the `zwp_primary_selection` protocol.
+    /// Selecting text in egui offers it as the primary selection, middle-clicking
+    /// a text field pastes the current primary selection of a client.
+    pub fn update_primary_selection<D>(
+        &self,
+        dh: &DisplayHandle,
+        seat: &Seat<D>,
+        user_data: <D as SelectionHandler>::SelectionUserData,
+    ) where
+        D: SeatHandler + PrimarySelectionHandler + 'static,
+    {
+        let mut inner = self.inner.lock().unwrap();
+        if std::mem::take(&mut inner.selections.primary.dirty) {
+            set_primary_selection(dh, seat, clipboard::text_mime_types(), user_data);
+        }
+
+        if std::mem::take(&mut inner.selections.primary.paste_requested) {
+            let requested = clipboard::request_text(|mime_type, fd| {
+                request_primary_client_selection(seat, mime_type, fd)
+            });
+            let fallback = inner.selections.primary.text.clone();
+            self.paste(&mut inner, requested, fallback);
+        }
+    }
+
+    fn paste(
+        &self,
+        inner: &mut EguiInner,
+        requested: Result<File, clipboard::RequestError>,
+        fallback: Option<String>,
+    ) {
+        match requested {
+            Ok(reader) => {
+                let state = self.inner.clone();
+                let ctx = self.ctx.clone();
+                clipboard::read_text(reader, move |text| {
+                    state.lock().unwrap().events.push(Event::Paste(text));
+                    ctx.request_repaint();
+                });
+            }
+            // the selection is owned by the compositor, which is likely egui itself
+            Err(clipboard::RequestError::Selection(SelectionRequestError::ServerSideSelection)) => {
+                match fallback {
+                    Some(text) => inner.events.push(Event::Paste(text)),
+                    None => log::debug!("Nothing to paste: no text was copied in egui"),
+                }
+            }
+            Err(err) => log::debug!("Nothing to paste: {}", err),
+        }
+    }
+
+    /// Send the text last copied or selected in egui to a client.
+    ///
+    /// Call this from your [`SelectionHandler::send_selection`] implementation for selections
+    /// set by [`EguiState::update_selection`] or [`EguiState::update_primary_selection`].
+    pub fn send_selection(&self, ty: SelectionTarget, mime_type: String, fd: OwnedFd) {
+        if !clipboard::is_text_mime_type(&mime_type) {
+            return;
+        }
+        let inner = self.inner.lock().unwrap();
+        let selection = match ty {
+            SelectionTarget::Clipboard => &inner.selections.clipboard,
+            SelectionTarget::Primary => &inner.selections.primary,
+        };
+        if let Some(text) = selection.text.clone() {
+            clipboard::write_text(fd, text);
+        }
+    }
+
     /// Set if this [`EguiState`] should consider itself focused
     pub fn set_focused(&self, focused: bool) {
         self.inner.lock().unwrap().focused = focused;
     }
 
-    // TODO: touch inputs
+    /// Set the handler for urls egui requests to open.
+    ///
+    /// Defaults to [`XdgOpen`]. Without a handler requests are left in [`EguiState::last_output`].
+    /// The handler is called after [`EguiState::render`] released its lock, so it may use
+    /// this `EguiState`, except for removing itself by passing `None` here.
+    pub fn set_url_handler(&self, handler: Option<Box<dyn UrlHandler>>) {
+        self.inner.lock().unwrap().url_handler = handler;
+    }
+
+    /// Set which touchpad gestures should be consumed by egui.
+    ///
+    /// The default translates pinches into zooming and swipes of up to three fingers into scrolling.
+    pub fn set_gesture_policy(&self, policy: GesturePolicy) {
+        self.inner.lock().unwrap().gesture_policy = policy;
+    }
+
+    /// Pass a new touch point to `EguiState`
+    ///
+    /// `device` identifies the touch device the `slot` belongs to.
+    /// The first active touch point is additionally emulated as a primary pointer button press.
+    pub fn handle_touch_down(
+        &self,
+        device: TouchDeviceId,
+        slot: TouchSlot,
+        position: Point<f64, Logical>,
+    ) {
+        let mut inner = self.inner.lock().unwrap();
+        inner
+            .touches
+            .retain(|t| t.device != device || t.slot != slot);
+        inner.touches.push(TouchPoint {
+            device,
+            slot,
+            position,
+        });
+        inner.push_touch(device, touch_id(slot), TouchPhase::Start, position);
+
+        if inner.pointer_touch.is_none() {
+            inner.pointer_touch = Some((device, slot));
+            inner.last_pointer_position = position;
+            let pos = inner.egui_pos(position);
+            let modifiers = convert_modifiers(inner.last_modifiers);
+            inner.events.push(Event::PointerMoved(pos));
+            inner.events.push(Event::PointerButton {
+                pos,
+                button: egui::PointerButton::Primary,
+                pressed: true,
+                modifiers,
+            });
+        }
+    }
+
+    /// Pass new coordinates of an existing touch point to `EguiState`
+    pub fn handle_touch_motion(
+        &self,
+        device: TouchDeviceId,
+        slot: TouchSlot,
+        position: Point<f64, Logical>,
+    ) {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(touch) = inner
+            .touches
+            .iter_mut()
+            .find(|t| t.device == device && t.slot == slot)
+        else {
+            return;
+        };
+        touch.position = position;
+        inner.push_touch(device, touch_id(slot), TouchPhase::Move, position);
+
+        if inner.pointer_touch == Some((device, slot)) {
+            inner.last_pointer_position = position;
+            let pos = inner.egui_pos(position);
+            inner.events.push(Event::PointerMoved(pos));
+        }
+    }
+
+    /// Pass the release of a touch point to `EguiState`
+    pub fn handle_touch_up(&self, device: TouchDeviceId, slot: TouchSlot) {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(idx) = inner
+            .touches
+            .iter()
+            .position(|t| t.device == device && t.slot == slot)
+        else {
+            return;
+        };
+        let touch = inner.touches.remove(idx);
+        inner.push_touch(device, touch_id(slot), TouchPhase::End, touch.position);
+
+        if inner.pointer_touch == Some((device, slot)) {
+            inner.pointer_touch = None;
+            let modifiers = convert_modifiers(inner.last_modifiers);
+            let pos = inner.egui_pos(touch.position);
+            inner.events.push(Event::PointerButton {
+                pos,
+                button: egui::PointerButton::Primary,
+                pressed: false,
+                modifiers,
+            });
+            // the pointer should vanish completely to not get any hover effects
+            inner.events.push(Event::PointerGone);
+        }
+    }
+
+    /// Cancel all active touch points of a touch device
+    pub fn handle_touch_cancel(&self, device: TouchDeviceId) {
+        let mut inner = self.inner.lock().unwrap();
+        let (cancelled, remaining) = std::mem::take(&mut inner.touches)
+            .into_iter()
+            .partition::<Vec<_>, _>(|t| t.device == device);
+        inner.touches = remaining;
+        for touch in cancelled {
+            inner.push_touch(
+                device,
+                touch_id(touch.slot),
+                TouchPhase::Cancel,
+                touch.position,
+            );
+        }
+
+        if matches!(inner.pointer_touch, Some((pointer_device, _)) if pointer_device == device) {
+            inner.pointer_touch = None;
+            // release the emulated button to not leave a drag behind,
+            // the pointer touch always moved the pointer last
+            let pos = inner.egui_pos(inner.last_pointer_position);
+            let modifiers = convert_modifiers(inner.last_modifiers);
+            inner.events.push(Event::PointerButton {
+                pos,
+                button: egui::PointerButton::Primary,
+                pressed: false,
+                modifiers,
+            });
+            inner.events.push(Event::PointerGone);
+        }
+    }
 
     /// Produce a new frame of egui. Returns a [`RenderElement`]
     ///
     /// - `ui` is your drawing function
     /// - `renderer` is a [`GlowRenderer`]
     /// - `area` limits the space egui will be using and offsets the result
-    /// - `scale` is the scale egui should render in
+    /// - `scale` is the scale egui should render in, which may be fractional
     /// - `alpha` applies (additional) transparency to the whole ui
     /// - `start_time` need to be a fixed point in time before the first `run` call to measure animation-times and the like.
     /// - `modifiers` should be the current state of modifiers pressed on the keyboards.
+    ///
+    /// If no input arrived and egui did not request a repaint, `ui` is not run
+    /// and the previous frame is returned unchanged.
+    /// Call [`Context::request_repaint`] if state displayed by `ui` changed outside of egui.
     pub fn render(
         &self,
         ui: impl FnMut(&Context),
@@ -284,14 +978,11 @@ impl EguiState {
         scale: f64,
         alpha: f32,
     ) -> Result<TextureRenderElement<GlesTexture>, GlesError> {
-        let int_scale = scale.ceil() as i32;
+        let spec = BufferSpec::new(area, scale);
         let user_data = renderer.egl_context().user_data();
         if user_data.get::<UserDataType>().is_none() {
             let painter = {
-                let mut frame = renderer.render(
-                    area.size.to_physical(int_scale),
-                    smithay::utils::Transform::Normal,
-                )?;
+                let mut frame = renderer.render(spec.size, smithay::utils::Transform::Normal)?;
                 frame
                     .with_context(|context| Painter::new(context.clone(), "", None, false))?
                     .map_err(|_| GlesError::ShaderCompileError)?
@@ -320,125 +1011,190 @@ impl EguiState {
             ..
         } = &mut *borrow;
 
-        let render_buffer = render_buffers.entry(self.id()).or_insert_with(|| {
-            let render_texture = renderer
-                .create_buffer(
-                    Fourcc::Abgr8888,
-                    area.size
-                        .to_buffer(int_scale, smithay::utils::Transform::Normal),
-                )
-                .expect("Failed to create buffer");
-            TextureRenderBuffer::from_texture(
-                renderer,
-                render_texture,
-                int_scale,
-                Transform::Flipped180,
-                None,
-            )
-        });
-
-        let screen_size: Size<i32, Physical> = area.size.to_physical(int_scale);
-        let input = RawInput {
-            screen_rect: Some(Rect {
-                min: Pos2 { x: 0.0, y: 0.0 },
-                max: Pos2 {
-                    x: screen_size.w as f32,
-                    y: screen_size.h as f32,
-                },
-            }),
-            time: Some(self.start_time.elapsed().as_secs_f64()),
-            modifiers: convert_modifiers(inner.last_modifiers),
-            events: inner.events.drain(..).collect(),
-            focused: inner.focused,
-            max_texture_side: Some(painter.max_texture_side()), // TODO query from GlState somehow
-            ..Default::default()
-        };
+        let created = !render_buffers.contains_key(&self.id());
+        if created {
+            let render_buffer = create_render_buffer(renderer, spec)?;
+            render_buffers.insert(self.id(), render_buffer);
+        }
+        let render_buffer = render_buffers.get_mut(&self.id()).unwrap();
 
-        let FullOutput {
-            platform_output,
-            shapes,
-            textures_delta,
-            ..
-        } = self.ctx.run(input.clone(), ui);
-        inner.last_output = Some(platform_output);
+        // nothing changed since the last frame, so the buffer is still up to date
+        let repaint_due = self
+            .next_repaint
+            .lock()
+            .unwrap()
+            .is_some_and(|next| next <= Instant::now());
+        let idle = !created
+            && !repaint_due
+            && inner.area == area
+            && inner.buffer_spec == Some(spec)
+            && inner.events.is_empty()
+            && inner.pending_axis.is_none()
+            && !inner.selections.query_primary;
+        if idle {
+            return Ok(render_element(render_buffer, area, scale, alpha));
+        }
 
-        let needs_recreate = inner.area != area;
-        inner.area = area;
+        let (
+            FullOutput {
+                shapes,
+                textures_delta,
+                ..
+            },
+            open_url,
+        ) = self.run_ui(
+            &mut inner,
+            area,
+            scale,
+            Some(painter.max_texture_side()), // TODO query from GlState somehow
+            ui,
+        );
+
+        let needs_recreate = inner.update_buffer_spec(area, spec) && !created;
+        // a new buffer has no previous frame to compare against
+        let needs_full_damage = created || needs_recreate;
 
         if needs_recreate {
-            *render_buffer = {
-                let render_texture = renderer.create_buffer(
-                    Fourcc::Abgr8888,
-                    area.size
-                        .to_buffer(int_scale, smithay::utils::Transform::Normal),
-                )?;
-                TextureRenderBuffer::from_texture(
-                    renderer,
-                    render_texture,
-                    int_scale,
-                    Transform::Flipped180,
-                    None,
-                )
-            };
+            *render_buffer = create_render_buffer(renderer, spec)?;
         }
 
+        let pixels_per_point = scale as f32;
+        let primitives = self.ctx.tessellate(shapes, pixels_per_point);
+        let digests = damage::digest(&primitives, &textures_delta);
+        let size = Rectangle::<i32, Physical>::from_loc_and_size((0, 0), spec.size);
+        let damage = if needs_full_damage {
+            vec![size]
+        } else {
+            damage::diff(&inner.last_primitives, &digests)
+                .into_iter()
+                .filter_map(|rect| {
+                    let rect = rect * pixels_per_point;
+                    Rectangle::<i32, Physical>::from_extemities(
+                        (rect.min.x.floor() as i32, rect.min.y.floor() as i32),
+                        (rect.max.x.ceil() as i32, rect.max.y.ceil() as i32),
+                    )
+                    .intersection(size)
+                })
+                .collect()
+        };
+        inner.last_primitives = digests;
+
         render_buffer.render().draw(|tex| {
             renderer.bind(tex.clone())?;
-            let physical_area = area.to_physical(int_scale);
             {
-                let mut frame = renderer.render(physical_area.size, Transform::Normal)?;
-                frame.clear([0.0, 0.0, 0.0, 0.0].into(), &[physical_area])?;
+                let mut frame = renderer.render(spec.size, Transform::Normal)?;
+                frame.clear(
+                    [0.0, 0.0, 0.0, 0.0].into(),
+                    &[Rectangle::from_loc_and_size((0, 0), spec.size)],
+                )?;
+                // egui lays out in logical points, which get rasterized at the exact output scale
                 painter.paint_and_update_textures(
-                    [physical_area.size.w as u32, physical_area.size.h as u32],
-                    int_scale as f32,
-                    &self.ctx.tessellate(shapes, int_scale as f32),
+                    [spec.size.w as u32, spec.size.h as u32],
+                    pixels_per_point,
+                    &primitives,
                     &textures_delta,
                 );
             }
             renderer.unbind()?;
 
-            let used = self.ctx.used_rect();
-            let margin = self.ctx.style().visuals.clip_rect_margin.ceil() as i32;
-            let window_shadow = self
-                .ctx
-                .style()
-                .visuals
-                .window_shadow
-                .margin()
-                .sum()
-                .max_elem()
-                .ceil() as i32;
-            let popup_shadow = self
-                .ctx
-                .style()
-                .visuals
-                .popup_shadow
-                .margin()
-                .sum()
-                .max_elem()
-                .ceil() as i32;
-            let offset = margin + Ord::max(window_shadow, popup_shadow);
-            Result::<_, GlesError>::Ok(vec![Rectangle::<i32, Logical>::from_extemities(
-                (
-                    (used.min.x.floor() as i32).saturating_sub(offset),
-                    (used.min.y.floor() as i32).saturating_sub(offset),
-                ),
-                (
-                    (used.max.x.ceil() as i32) + (offset * 2),
-                    (used.max.y.ceil() as i32) + (offset * 2),
-                ),
+            Result::<_, GlesError>::Ok(
+                damage
+                    .iter()
+                    .map(|rect| {
+                        let rect = BUFFER_TRANSFORM.transform_rect_in(*rect, &spec.size);
+                        Rectangle::<i32, Buffer>::from_loc_and_size(
+                            (rect.loc.x, rect.loc.y),
+                            (rect.size.w, rect.size.h),
+                        )
+                    })
+                    .collect::<Vec<_>>(),
             )
-            .to_buffer(int_scale, Transform::Flipped180, &area.size)])
         })?;
 
-        Ok(TextureRenderElement::from_texture_render_buffer(
-            area.loc.to_f64().to_physical(scale),
-            &render_buffer,
-            Some(alpha),
-            None,
-            None,
-            Kind::Unspecified,
-        ))
+        let element = render_element(render_buffer, area, scale, alpha);
+        std::mem::drop(borrow);
+        std::mem::drop(inner);
+        if let Some(open_url) = open_url {
+            self.open_url(&open_url);
+        }
+        Ok(element)
+    }
+
+    // Runs `ui` on the queued input and handles the platform output of the frame.
+    // Urls to open are returned to call the url handler once `inner` is unlocked.
+    fn run_ui(
+        &self,
+        inner: &mut EguiInner,
+        area: Rectangle<i32, Logical>,
+        scale: f64,
+        max_texture_side: Option<usize>,
+        ui: impl FnMut(&Context),
+    ) -> (FullOutput, Option<egui::OpenUrl>) {
+        // compositors not sending `frame` events still expect their scrolling to arrive
+        inner.flush_axis();
+
+        // egui offers no access to selected text, but copies it for us.
+        // Wait for frames without a copy request of the user to not mix up both selections.
+        let mut query_primary = false;
+        if inner.selections.query_primary
+            && !inner
+                .events
+                .iter()
+                .any(|event| matches!(event, Event::Copy | Event::Cut))
+        {
+            inner.selections.query_primary = false;
+            // without a selection egui copies the whole text
+            query_primary = clipboard::text_selection(&self.ctx).is_some();
+            if query_primary {
+                inner.events.push(Event::Copy);
+            }
+        }
+
+        let input = inner.raw_input(self.start_time, area, scale, max_texture_side);
+
+        // collect the repaint requests made while running egui
+        *self.next_repaint.lock().unwrap() = None;
+        let mut output = self.ctx.run(input, ui);
+        if let Some(output) = output.viewport_output.get(&ViewportId::ROOT) {
+            request_repaint(&self.next_repaint, output.repaint_delay);
+        }
+
+        let mut platform_output = std::mem::take(&mut output.platform_output);
+        let open_url = inner
+            .url_handler
+            .is_some()
+            .then(|| platform_output.open_url.take())
+            .flatten();
+        if query_primary {
+            // not a copy of the user, so keep it out of the clipboard of `last_output`
+            let text = std::mem::take(&mut platform_output.copied_text);
+            // the input of this frame might have cleared the selection
+            if !text.is_empty() && clipboard::text_selection(&self.ctx).is_some() {
+                inner.selections.primary.copy(text);
+            }
+        } else if !platform_output.copied_text.is_empty() {
+            let text = platform_output.copied_text.clone();
+            inner.selections.clipboard.copy(text);
+        }
+        inner.selections.update_selected(&self.ctx);
+        inner.hovering_text = platform_output.cursor_icon == egui::CursorIcon::Text;
+        inner.cursor_icon = platform_output.cursor_icon;
+        inner.last_output = Some(platform_output);
+        (output, open_url)
+    }
+
+    fn open_url(&self, open_url: &egui::OpenUrl) {
+        // call the handler without holding the lock, so that it may use this `EguiState`
+        let handler = self.inner.lock().unwrap().url_handler.take();
+        let Some(mut handler) = handler else {
+            return;
+        };
+        handler.open_url(open_url);
+        let mut inner = self.inner.lock().unwrap();
+        // a handler set in the meantime replaces this one
+        if inner.url_handler.is_none() {
+            inner.url_handler = Some(handler);
+        }
     }
 
     #[cfg(all(feature = "image", any(feature = "png", feature = "jpg")))]
@@ -545,93 +1301,556 @@ impl EguiState {
         self.inner.lock().unwrap().z_index = idx;
     }
 
-    /// Returns the egui [`PlatformOutput`] generated by the last [`Self::render`] call
-    pub fn last_output(&self) -> Option<PlatformOutput> {
+    /// Returns when [`EguiState::render`] needs to be called next to keep egui up to date.
+    ///
+    /// Check this after every [`EguiState::render`] call and after passing input to schedule the next frame.
+    /// Rendering earlier returns the previous frame without any work.
+    ///
+    /// While a key is held, the deadline includes its next repeat.
+    /// Call [`EguiState::handle_key_repeat`] before rendering to generate it.
+    pub fn next_repaint(&self) -> RepaintDeadline {
+        let key_repeat = {
+            let inner = self.inner.lock().unwrap();
+            if !inner.events.is_empty()
+                || inner.pending_axis.is_some()
+                || inner.selections.query_primary
+            {
+                return RepaintDeadline::Immediately;
+            }
+            inner.key_repeat.as_ref().map(|repeat| repeat.deadline)
+        };
+        let next_repaint = *self.next_repaint.lock().unwrap();
+        match next_repaint.into_iter().chain(key_repeat).min() {
+            Some(next) if next <= Instant::now() => RepaintDeadline::Immediately,
+            Some(next) => RepaintDeadline::At(next),
+            None => RepaintDeadline::Never,
+        }
+    }
+
+    /// Returns the egui [`PlatformOutput`] generated by the last [`Self::render`] call
+    pub fn last_output(&self) -> Option<PlatformOutput> {
         self.inner.lock().unwrap().last_output.take()
     }
 }
 
+impl EguiInner {
+    // Stores the area and buffer of the next frame, returns true if the previous buffer can't be reused.
+    // Moving the area keeps the buffer, any change of its contents' size or layout does not.
+    fn update_buffer_spec(&mut self, area: Rectangle<i32, Logical>, spec: BufferSpec) -> bool {
+        self.area = area;
+        self.buffer_spec.replace(spec) != Some(spec)
+    }
+
+    fn replace_kbd(&mut self, mut kbd: input::KbdInternal) {
+        // keep the new state in sync with keys that are already pressed
+        for (_, code) in &self.pressed {
+            kbd.key_input(code.raw(), true);
+        }
+        kbd.update_modifiers(&self.last_modifiers);
+        if let Some(old) = self.kbd.take() {
+            kbd.take_compose(old);
+        }
+        self.kbd = Some(kbd);
+    }
+
+    fn release_buttons(&mut self) {
+        let pos = self.egui_pos(self.last_pointer_position);
+        let modifiers = convert_modifiers(self.last_modifiers);
+        for button in std::mem::take(&mut self.pressed_buttons) {
+            self.events.push(Event::PointerButton {
+                pos,
+                button,
+                pressed: false,
+                modifiers,
+            });
+        }
+    }
+
+    // returns if the modifiers changed, which cancels key repeat
+    fn update_modifiers(&mut self, modifiers: ModifiersState) -> bool {
+        let changed = self.last_modifiers != modifiers;
+        if changed {
+            self.key_repeat = None;
+        }
+        self.last_modifiers = modifiers;
+        changed
+    }
+
+    // feeds a key to the keymap state and queues the text its press generates
+    fn key_input(
+        &mut self,
+        code: Keycode,
+        pressed: bool,
+        modifiers: Option<&ModifiersState>,
+    ) -> Option<String> {
+        let kbd = self.kbd.as_mut()?;
+        kbd.key_input(code.raw(), pressed);
+        if let Some(modifiers) = modifiers {
+            kbd.update_modifiers(modifiers);
+        }
+        if !pressed {
+            return None;
+        }
+
+        /* text contains the utf8 string generated by that keystroke
+         * it can contain 1 or multiple characters and is only
+         * produced once a dead key or compose sequence is finished
+         */
+        let text = kbd.key_text(code.raw());
+        if let Some(text) = text.as_ref() {
+            self.events.push(Event::Text(text.clone()));
+        }
+        text
+    }
+
+    fn start_key_repeat(
+        &mut self,
+        key: Option<egui::Key>,
+        code: Keycode,
+        text: Option<String>,
+        time: u32,
+    ) {
+        if self.repeat_rate > 0 && (key.is_some() || text.is_some()) {
+            let delay = self.repeat_delay.max(0) as u32;
+            self.key_repeat = Some(KeyRepeat {
+                key,
+                code,
+                text,
+                next: time.wrapping_add(delay),
+                deadline: Instant::now() + Duration::from_millis(delay.into()),
+            });
+        }
+    }
+
+    fn stop_key_repeat(&mut self, code: Keycode) {
+        if self
+            .key_repeat
+            .as_ref()
+            .is_some_and(|repeat| repeat.code == code)
+        {
+            self.key_repeat = None;
+        }
+    }
+
+    fn key_repeat(&mut self, time: u32) -> Option<u32> {
+        let interval = (1000 / self.repeat_rate.max(1)).max(1) as u32;
+        let modifiers = convert_modifiers(self.last_modifiers);
+        let repeat = self.key_repeat.as_mut()?;
+
+        if time.wrapping_sub(repeat.next) as i32 >= 0 {
+            if let Some(key) = repeat.key {
+                self.events.push(Event::Key {
+                    key,
+                    physical_key: convert_physical_key(repeat.code),
+                    pressed: true,
+                    repeat: true,
+                    modifiers,
+                });
+            }
+            if let Some(text) = repeat.text.as_ref() {
+                self.events.push(Event::Text(text.clone()));
+            }
+            // skip intervals missed by calling late
+            repeat.next = time.wrapping_add(interval);
+            repeat.deadline = Instant::now() + Duration::from_millis(interval.into());
+        }
+
+        Some(repeat.next)
+    }
+
+    fn axis_frame(&mut self, frame: &AxisFrame) {
+        let discrete = matches!(
+            frame.source,
+            Some(AxisSource::Wheel) | Some(AxisSource::WheelTilt)
+        );
+        // wayland scrolls "down" on positive values, while egui expects the content offset.
+        let (unit, delta) = match frame.v120 {
+            Some((x, y)) if discrete => (
+                egui::MouseWheelUnit::Line,
+                Vec2::new(-x as f32 / 120.0, -y as f32 / 120.0),
+            ),
+            _ => (
+                egui::MouseWheelUnit::Point,
+                Vec2::new(-frame.axis.0 as f32, -frame.axis.1 as f32),
+            ),
+        };
+
+        match self.pending_axis.as_mut() {
+            Some((pending_unit, pending_delta)) if *pending_unit == unit => {
+                *pending_delta += delta;
+            }
+            _ => {
+                self.flush_axis();
+                self.pending_axis = Some((unit, delta));
+            }
+        }
+
+        if frame.stop.0 || frame.stop.1 {
+            self.flush_axis();
+        }
+    }
+
+    fn swipe(&mut self, delta: Point<f64, Logical>) {
+        self.flush_axis();
+        let modifiers = convert_modifiers(self.last_modifiers);
+        // negated like scrolling in `axis_frame`, so both move content the same way
+        self.events.push(Event::MouseWheel {
+            unit: egui::MouseWheelUnit::Point,
+            delta: Vec2::new(-delta.x as f32, -delta.y as f32),
+            modifiers,
+        });
+    }
+
+    fn flush_axis(&mut self) {
+        if let Some((unit, delta)) = self.pending_axis.take() {
+            if delta != Vec2::ZERO {
+                let modifiers = convert_modifiers(self.last_modifiers);
+                self.events.push(Event::MouseWheel {
+                    unit,
+                    delta,
+                    modifiers,
+                });
+            }
+        }
+    }
+
+    fn push_pinch_touches(&mut self, phase: TouchPhase, scale: f64, rotation: f64) {
+        let center = self.last_pointer_position;
+        let radius = PINCH_TOUCH_RADIUS * scale;
+        let (sin, cos) = rotation.to_radians().sin_cos();
+        for (id, sign) in [(0, 1.0), (1, -1.0)] {
+            let position = Point::from((
+                center.x + sign * radius * cos,
+                center.y + sign * radius * sin,
+            ));
+            self.push_touch(PINCH_TOUCH_DEVICE, TouchId(id), phase, position);
+        }
+    }
+
+    fn push_touch(
+        &mut self,
+        device: TouchDeviceId,
+        id: TouchId,
+        phase: TouchPhase,
+        position: Point<f64, Logical>,
+    ) {
+        let pos = self.egui_pos(position);
+        self.events.push(Event::Touch {
+            device_id: device,
+            id,
+            phase,
+            pos,
+            force: None,
+        });
+    }
+
+    // converts logical input coordinates into egui points relative to `area`,
+    // keeping sub-pixel precision. One egui point equals one logical pixel.
+    fn egui_pos(&self, position: Point<f64, Logical>) -> Pos2 {
+        let local = position - self.area.loc.to_f64();
+        Pos2::new(local.x as f32, local.y as f32)
+    }
+
+    fn raw_input(
+        &mut self,
+        start_time: Instant,
+        area: Rectangle<i32, Logical>,
+        scale: f64,
+        max_texture_side: Option<usize>,
+    ) -> RawInput {
+        let viewport = ViewportInfo {
+            native_pixels_per_point: Some(scale as f32),
+            ..Default::default()
+        };
+        RawInput {
+            viewports: std::iter::once((ViewportId::ROOT, viewport)).collect(),
+            screen_rect: Some(Rect {
+                min: Pos2 { x: 0.0, y: 0.0 },
+                max: Pos2 {
+                    x: area.size.w as f32,
+                    y: area.size.h as f32,
+                },
+            }),
+            time: Some(start_time.elapsed().as_secs_f64()),
+            modifiers: convert_modifiers(self.last_modifiers),
+            events: self.events.drain(..).collect(),
+            focused: self.focused,
+            max_texture_side,
+            ..Default::default()
+        }
+    }
+}
+
 impl IsAlive for EguiState {
     fn alive(&self) -> bool {
         true
     }
 }
 
+impl EguiState {
+    // seat events are relative to the focus, which is located at the top-left corner of `area`
+    fn seat_location(&self, location: Point<f64, Logical>) -> Point<f64, Logical> {
+        location + self.inner.lock().unwrap().area.loc.to_f64()
+    }
+
+    fn apply_cursor_image<D: SeatHandler>(&self, seat: &Seat<D>, data: &mut D) {
+        let icon = {
+            let mut inner = self.inner.lock().unwrap();
+            if inner.applied_cursor_icon == Some(inner.cursor_icon) {
+                return;
+            }
+            inner.applied_cursor_icon = Some(inner.cursor_icon);
+            inner.cursor_icon
+        };
+        data.cursor_image(seat, convert_cursor_icon(icon));
+    }
+}
+
 impl<D: SeatHandler> PointerTarget<D> for EguiState {
-    fn enter(&self, _seat: &Seat<D>, _data: &mut D, event: &MotionEvent) {
-        self.handle_pointer_motion(event.location.to_i32_floor())
+    fn enter(&self, seat: &Seat<D>, data: &mut D, event: &MotionEvent) {
+        {
+            let mut inner = self.inner.lock().unwrap();
+            inner.applied_cursor_icon = None;
+            // drop buttons left over from a leave we never got notified about
+            inner.release_buttons();
+        }
+        self.handle_pointer_motion(self.seat_location(event.location));
+        self.apply_cursor_image(seat, data);
     }
 
-    fn motion(&self, _seat: &Seat<D>, _data: &mut D, event: &MotionEvent) {
-        self.handle_pointer_motion(event.location.to_i32_round())
+    fn motion(&self, seat: &Seat<D>, data: &mut D, event: &MotionEvent) {
+        self.handle_pointer_motion(self.seat_location(event.location));
+        self.apply_cursor_image(seat, data);
     }
 
     fn relative_motion(&self, _seat: &Seat<D>, _data: &mut D, _event: &RelativeMotionEvent) {}
 
     fn button(&self, _seat: &Seat<D>, _data: &mut D, event: &ButtonEvent) {
-        if let Some(button) = match event.button {
-            0x110 => Some(MouseButton::Left),
-            0x111 => Some(MouseButton::Right),
-            0x112 => Some(MouseButton::Middle),
-            0x115 => Some(MouseButton::Forward),
-            0x116 => Some(MouseButton::Back),
-            _ => None,
-        } {
+        if let Some(button) = convert_button_code(event.button) {
             self.handle_pointer_button(button, event.state == ButtonState::Pressed)
         }
     }
 
-    fn axis(&self, _seat: &Seat<D>, _data: &mut D, _frame: AxisFrame) {
-        // TODO
-        //self.handle_pointer_axis(frame., y_amount)
+    fn axis(&self, _seat: &Seat<D>, _data: &mut D, frame: AxisFrame) {
+        self.inner.lock().unwrap().axis_frame(&frame)
     }
 
-    fn leave(&self, _seat: &Seat<D>, _data: &mut D, _serial: Serial, _time: u32) {}
+    fn leave(&self, _seat: &Seat<D>, _data: &mut D, _serial: Serial, _time: u32) {
+        self.handle_pointer_leave()
+    }
 
-    fn frame(&self, _seat: &Seat<D>, _data: &mut D) {}
+    fn frame(&self, _seat: &Seat<D>, _data: &mut D) {
+        self.inner.lock().unwrap().flush_axis()
+    }
 
-    fn gesture_swipe_begin(&self, _seat: &Seat<D>, _data: &mut D, _event: &GestureSwipeBeginEvent) {
+    fn gesture_swipe_begin(&self, _seat: &Seat<D>, _data: &mut D, event: &GestureSwipeBeginEvent) {
+        let mut inner = self.inner.lock().unwrap();
+        let policy = inner.gesture_policy;
+        inner.gesture = (policy.swipe_scroll && event.fingers <= policy.swipe_max_fingers)
+            .then_some(Gesture::Swipe);
     }
 
     fn gesture_swipe_update(
         &self,
         _seat: &Seat<D>,
         _data: &mut D,
-        _event: &GestureSwipeUpdateEvent,
+        event: &GestureSwipeUpdateEvent,
     ) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(Gesture::Swipe) = inner.gesture {
+            inner.swipe(event.delta);
+        }
     }
 
-    fn gesture_swipe_end(&self, _seat: &Seat<D>, _data: &mut D, _event: &GestureSwipeEndEvent) {}
+    fn gesture_swipe_end(&self, _seat: &Seat<D>, _data: &mut D, _event: &GestureSwipeEndEvent) {
+        self.inner.lock().unwrap().gesture = None;
+    }
 
     fn gesture_pinch_begin(&self, _seat: &Seat<D>, _data: &mut D, _event: &GesturePinchBeginEvent) {
+        let mut inner = self.inner.lock().unwrap();
+        let policy = inner.gesture_policy;
+        if !policy.pinch_zoom && !policy.pinch_rotation {
+            inner.gesture = None;
+            return;
+        }
+
+        inner.gesture = Some(Gesture::Pinch {
+            scale: 1.0,
+            rotation: 0.0,
+        });
+        if policy.pinch_rotation {
+            inner.push_pinch_touches(TouchPhase::Start, 1.0, 0.0);
+        }
     }
 
     fn gesture_pinch_update(
         &self,
         _seat: &Seat<D>,
         _data: &mut D,
-        _event: &GesturePinchUpdateEvent,
+        event: &GesturePinchUpdateEvent,
     ) {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(Gesture::Pinch { scale, rotation }) = inner.gesture else {
+            return;
+        };
+        let policy = inner.gesture_policy;
+
+        // the scale of the event is relative to the beginning of the gesture, egui wants the change
+        if policy.pinch_zoom && scale > 0.0 && event.scale > 0.0 {
+            inner.events.push(Event::Zoom((event.scale / scale) as f32));
+        }
+        let rotation = rotation + event.rotation;
+        inner.gesture = Some(Gesture::Pinch {
+            scale: event.scale,
+            rotation,
+        });
+        if policy.pinch_rotation {
+            inner.push_pinch_touches(TouchPhase::Move, event.scale, rotation);
+        }
     }
 
-    fn gesture_pinch_end(&self, _seat: &Seat<D>, _data: &mut D, _event: &GesturePinchEndEvent) {}
+    fn gesture_pinch_end(&self, _seat: &Seat<D>, _data: &mut D, event: &GesturePinchEndEvent) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(Gesture::Pinch { scale, rotation }) = inner.gesture.take() {
+            if inner.gesture_policy.pinch_rotation {
+                let phase = if event.cancelled {
+                    TouchPhase::Cancel
+                } else {
+                    TouchPhase::End
+                };
+                inner.push_pinch_touches(phase, scale, rotation);
+            }
+        }
+    }
 
     fn gesture_hold_begin(&self, _seat: &Seat<D>, _data: &mut D, _event: &GestureHoldBeginEvent) {}
 
     fn gesture_hold_end(&self, _seat: &Seat<D>, _data: &mut D, _event: &GestureHoldEndEvent) {}
 }
 
+// offscreen rendering is upside down
+const BUFFER_TRANSFORM: Transform = Transform::Flipped180;
+
+// everything the render buffer of a frame depends on
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BufferSpec {
+    // covers exactly the pixels egui occupies on the output
+    size: Size<i32, Physical>,
+    scale: f64,
+}
+
+impl BufferSpec {
+    fn new(area: Rectangle<i32, Logical>, scale: f64) -> Self {
+        BufferSpec {
+            size: area.size.to_physical_precise_round(scale),
+            scale,
+        }
+    }
+
+    fn buffer_size(&self) -> Size<i32, Buffer> {
+        let size = BUFFER_TRANSFORM.transform_size(self.size);
+        (size.w, size.h).into()
+    }
+}
+
+// the buffer has a scale of 1, so that it matches fractional output scales pixel by pixel
+fn create_render_buffer(
+    renderer: &mut GlowRenderer,
+    spec: BufferSpec,
+) -> Result<TextureRenderBuffer<GlesTexture>, GlesError> {
+    let render_texture = renderer.create_buffer(Fourcc::Abgr8888, spec.buffer_size())?;
+    Ok(TextureRenderBuffer::from_texture(
+        renderer,
+        render_texture,
+        1,
+        BUFFER_TRANSFORM,
+        None,
+    ))
+}
+
+fn render_element(
+    render_buffer: &TextureRenderBuffer<GlesTexture>,
+    area: Rectangle<i32, Logical>,
+    scale: f64,
+    alpha: f32,
+) -> TextureRenderElement<GlesTexture> {
+    // stretch the buffer over the logical area, which at `scale` is exactly the buffer's size
+    TextureRenderElement::from_texture_render_buffer(
+        area.loc.to_f64().to_physical(scale),
+        render_buffer,
+        Some(alpha),
+        None,
+        Some(area.size),
+        Kind::Unspecified,
+    )
+}
+
+// moves the deadline of the next repaint forward to `delay` from now
+fn request_repaint(next_repaint: &Mutex<Option<Instant>>, delay: Duration) {
+    let Some(deadline) = Instant::now().checked_add(delay) else {
+        return;
+    };
+    let mut next_repaint = next_repaint.lock().unwrap();
+    *next_repaint = Some(match *next_repaint {
+        Some(next) => next.min(deadline),
+        None => deadline,
+    });
+}
+
+fn touch_id(slot: TouchSlot) -> TouchId {
+    TouchId(Option::<u32>::from(slot).map_or(u64::MAX, u64::from))
+}
+
+fn touch_device_id<D: SeatHandler>(seat: &Seat<D>) -> TouchDeviceId {
+    TouchDeviceId(egui::epaint::util::hash(seat.name()))
+}
+
+impl<D: SeatHandler> TouchTarget<D> for EguiState {
+    fn down(&self, seat: &Seat<D>, _data: &mut D, event: &DownEvent, _seq: Serial) {
+        self.handle_touch_down(
+            touch_device_id(seat),
+            event.slot,
+            self.seat_location(event.location),
+        )
+    }
+
+    fn up(&self, seat: &Seat<D>, _data: &mut D, event: &UpEvent, _seq: Serial) {
+        self.handle_touch_up(touch_device_id(seat), event.slot)
+    }
+
+    fn motion(&self, seat: &Seat<D>, _data: &mut D, event: &TouchMotionEvent, _seq: Serial) {
+        self.handle_touch_motion(
+            touch_device_id(seat),
+            event.slot,
+            self.seat_location(event.location),
+        )
+    }
+
+    fn frame(&self, _seat: &Seat<D>, _data: &mut D, _seq: Serial) {}
+
+    fn cancel(&self, seat: &Seat<D>, _data: &mut D, _seq: Serial) {
+        self.handle_touch_cancel(touch_device_id(seat))
+    }
+
+    fn shape(&self, _seat: &Seat<D>, _data: &mut D, _event: &ShapeEvent, _seq: Serial) {}
+
+    fn orientation(&self, _seat: &Seat<D>, _data: &mut D, _event: &OrientationEvent, _seq: Serial) {
+    }
+}
+
 impl<D: SeatHandler> KeyboardTarget<D> for EguiState {
     fn enter(&self, _seat: &Seat<D>, _data: &mut D, keys: Vec<KeysymHandle<'_>>, _serial: Serial) {
         self.set_focused(true);
 
         let mut inner = self.inner.lock().unwrap();
         for handle in &keys {
-            let key = if let Some(key) = convert_key(handle.raw_syms().iter().copied()) {
+            let key = if let Some((key, physical_key)) =
+                convert_key_event(handle.raw_syms().iter().copied(), handle.raw_code())
+            {
                 let modifiers = convert_modifiers(inner.last_modifiers);
                 inner.events.push(Event::Key {
                     key,
-                    physical_key: None,
+                    physical_key,
                     pressed: true,
                     repeat: false,
                     modifiers,
@@ -657,7 +1876,7 @@ impl<D: SeatHandler> KeyboardTarget<D> for EguiState {
                 let modifiers = convert_modifiers(inner.last_modifiers);
                 inner.events.push(Event::Key {
                     key,
-                    physical_key: None,
+                    physical_key: convert_physical_key(code),
                     pressed: false,
                     repeat: false,
                     modifiers,
@@ -667,6 +1886,10 @@ impl<D: SeatHandler> KeyboardTarget<D> for EguiState {
                 kbd.key_input(code.raw(), false);
             }
         }
+        if let Some(kbd) = inner.kbd.as_mut() {
+            kbd.reset_compose();
+        }
+        inner.key_repeat = None;
     }
 
     fn key(
@@ -676,10 +1899,10 @@ impl<D: SeatHandler> KeyboardTarget<D> for EguiState {
         key: KeysymHandle<'_>,
         state: KeyState,
         _serial: Serial,
-        _time: u32,
+        time: u32,
     ) {
         let modifiers = self.inner.lock().unwrap().last_modifiers;
-        self.handle_keyboard(&key, state == KeyState::Pressed, modifiers)
+        self.handle_keyboard(&key, state == KeyState::Pressed, modifiers, time)
     }
 
     fn modifiers(
@@ -689,20 +1912,28 @@ impl<D: SeatHandler> KeyboardTarget<D> for EguiState {
         modifiers: ModifiersState,
         _serial: Serial,
     ) {
-        self.inner.lock().unwrap().last_modifiers = modifiers;
+        let mut inner = self.inner.lock().unwrap();
+        inner.update_modifiers(modifiers);
+        if let Some(kbd) = inner.kbd.as_mut() {
+            kbd.update_modifiers(&modifiers);
+        }
     }
 }
 
 #[cfg(feature = "desktop_integration")]
 impl SpaceElement for EguiState {
     fn bbox(&self) -> Rectangle<i32, Logical> {
-        self.inner.lock().unwrap().area
+        // relative to the location the element is mapped at, which should be the location of `area`
+        let size = self.inner.lock().unwrap().area.size;
+        Rectangle::from_loc_and_size((0, 0), size)
     }
 
     fn is_in_input_region(&self, point: &Point<f64, Logical>) -> bool {
-        let pos: Point<i32, _> = point.to_i32_round();
-        let last_pos = self.inner.lock().unwrap().last_pointer_position;
-        if (pos.x - last_pos.x) + (pos.y - last_pos.y) < 10 {
+        let last_pos = {
+            let inner = self.inner.lock().unwrap();
+            inner.last_pointer_position - inner.area.loc.to_f64()
+        };
+        if (point.x - last_pos.x).abs() + (point.y - last_pos.y).abs() < 10.0 {
             self.wants_pointer()
         } else {
             false
@@ -717,3 +1948,706 @@ impl SpaceElement for EguiState {
         self.inner.lock().unwrap().z_index as u8
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use smithay::backend::input::Axis;
+
+    const SCALES: [f64; 3] = [1.0, 1.5, 2.0];
+
+    #[derive(Debug, PartialEq, Eq, Hash)]
+    struct FakeDevice {
+        id: &'static str,
+        capabilities: &'static [DeviceCapability],
+    }
+
+    impl Device for FakeDevice {
+        fn id(&self) -> String {
+            self.id.to_string()
+        }
+
+        fn name(&self) -> String {
+            self.id.to_string()
+        }
+
+        fn has_capability(&self, capability: DeviceCapability) -> bool {
+            self.capabilities.contains(&capability)
+        }
+
+        fn usb_id(&self) -> Option<(u32, u32)> {
+            None
+        }
+
+        fn syspath(&self) -> Option<std::path::PathBuf> {
+            None
+        }
+    }
+
+    const MOUSE: FakeDevice = FakeDevice {
+        id: "mouse",
+        capabilities: &[DeviceCapability::Pointer],
+    };
+    const TOUCHPAD: FakeDevice = FakeDevice {
+        id: "touchpad",
+        capabilities: &[DeviceCapability::Pointer, DeviceCapability::Gesture],
+    };
+    const TOUCHSCREEN: FakeDevice = FakeDevice {
+        id: "touchscreen",
+        capabilities: &[DeviceCapability::Touch],
+    };
+    const TABLET: FakeDevice = FakeDevice {
+        id: "tablet",
+        capabilities: &[DeviceCapability::TabletTool],
+    };
+    const KEYBOARD: FakeDevice = FakeDevice {
+        id: "keyboard",
+        capabilities: &[DeviceCapability::Keyboard],
+    };
+
+    fn pointer_gone(egui: &EguiState) -> bool {
+        egui.inner
+            .lock()
+            .unwrap()
+            .events
+            .drain(..)
+            .any(|event| matches!(event, Event::PointerGone))
+    }
+
+    fn area() -> Rectangle<i32, Logical> {
+        Rectangle::from_loc_and_size((100, 50), (400, 300))
+    }
+
+    fn run(egui: &EguiState, scale: f64) {
+        let input = egui
+            .inner
+            .lock()
+            .unwrap()
+            .raw_input(egui.start_time, area(), scale, None);
+        let _ = egui.ctx.run(input, |_| {});
+    }
+
+    #[test]
+    fn screen_rect_is_logical() {
+        for scale in SCALES {
+            let egui = EguiState::new(area());
+            run(&egui, scale);
+            assert_eq!(egui.ctx.pixels_per_point(), scale as f32);
+            assert_eq!(egui.ctx.screen_rect().size(), Vec2::new(400.0, 300.0));
+        }
+    }
+
+    #[test]
+    fn keyboard_does_not_zoom() {
+        for scale in SCALES {
+            let egui = EguiState::new(area());
+            run(&egui, scale);
+            for key in [egui::Key::Minus, egui::Key::Plus, egui::Key::Equals] {
+                egui.inner.lock().unwrap().events.push(Event::Key {
+                    key,
+                    physical_key: None,
+                    pressed: true,
+                    repeat: false,
+                    modifiers: egui::Modifiers::COMMAND,
+                });
+                run(&egui, scale);
+                assert_eq!(egui.ctx.pixels_per_point(), scale as f32, "{:?}", key);
+            }
+        }
+    }
+
+    #[test]
+    fn pointer_is_area_local() {
+        for scale in SCALES {
+            let egui = EguiState::new(area());
+            egui.handle_pointer_motion((110.5, 60.25).into());
+            run(&egui, scale);
+            let pos = egui.ctx.input(|i| i.pointer.latest_pos());
+            assert_eq!(pos, Some(Pos2::new(10.5, 10.25)), "scale {}", scale);
+        }
+    }
+
+    #[test]
+    fn button_is_area_local() {
+        for scale in SCALES {
+            let egui = EguiState::new(area());
+            egui.handle_pointer_motion((300.0, 200.0).into());
+            egui.handle_pointer_button(MouseButton::Left, true);
+            run(&egui, scale);
+            let origin = egui.ctx.input(|i| i.pointer.press_origin());
+            assert_eq!(origin, Some(Pos2::new(200.0, 150.0)), "scale {}", scale);
+        }
+    }
+
+    #[test]
+    fn touch_cancel_releases_pointer() {
+        let egui = EguiState::new(area());
+        let device = TouchDeviceId(0);
+        egui.handle_touch_down(device, TouchSlot::from(Some(0)), (150.0, 80.0).into());
+        run(&egui, 1.0);
+        assert!(egui.ctx.input(|i| i.pointer.primary_down()));
+
+        egui.handle_touch_cancel(device);
+        {
+            let inner = egui.inner.lock().unwrap();
+            assert!(matches!(
+                inner.events.as_slice(),
+                [
+                    Event::Touch {
+                        phase: TouchPhase::Cancel,
+                        ..
+                    },
+                    Event::PointerButton { pressed: false, .. },
+                    Event::PointerGone,
+                ]
+            ));
+        }
+        run(&egui, 1.0);
+        assert!(!egui.ctx.input(|i| i.pointer.primary_down()));
+    }
+
+    #[test]
+    fn touch_is_area_local() {
+        for scale in SCALES {
+            let egui = EguiState::new(area());
+            egui.handle_touch_down(
+                TouchDeviceId(0),
+                TouchSlot::from(Some(0)),
+                (150.0, 80.0).into(),
+            );
+            run(&egui, scale);
+            let pos = egui.ctx.input(|i| i.pointer.latest_pos());
+            assert_eq!(pos, Some(Pos2::new(50.0, 30.0)), "scale {}", scale);
+        }
+    }
+
+    #[test]
+    fn unknown_device_removal() {
+        let egui = EguiState::new(area());
+        for device in [MOUSE, TOUCHSCREEN, KEYBOARD] {
+            egui.handle_device_removed(&device);
+        }
+        assert!(!pointer_gone(&egui));
+
+        egui.handle_device_added(&MOUSE);
+        egui.handle_device_removed(&TOUCHPAD);
+        assert!(!pointer_gone(&egui));
+    }
+
+    #[test]
+    fn pointer_gone_with_last_device() {
+        let egui = EguiState::new(area());
+        for device in [MOUSE, TOUCHPAD, TOUCHSCREEN, TABLET] {
+            egui.handle_device_added(&device);
+        }
+        for device in [MOUSE, TOUCHPAD, TOUCHSCREEN] {
+            egui.handle_device_removed(&device);
+            assert!(
+                !pointer_gone(&egui),
+                "{} was not the last device",
+                device.id
+            );
+        }
+        egui.handle_device_removed(&TABLET);
+        assert!(pointer_gone(&egui));
+    }
+
+    #[test]
+    fn keyboards_are_ignored() {
+        let egui = EguiState::new(area());
+        egui.handle_device_added(&KEYBOARD);
+        egui.handle_device_added(&MOUSE);
+        egui.handle_device_removed(&KEYBOARD);
+        assert!(!pointer_gone(&egui));
+        egui.handle_device_removed(&MOUSE);
+        assert!(pointer_gone(&egui));
+    }
+
+    #[test]
+    fn devices_are_tracked_by_id() {
+        let egui = EguiState::new(area());
+        egui.handle_device_added(&MOUSE);
+        egui.handle_device_added(&MOUSE);
+        egui.handle_device_removed(&MOUSE);
+        assert!(pointer_gone(&egui));
+        egui.handle_device_removed(&MOUSE);
+        assert!(!pointer_gone(&egui));
+    }
+
+    #[test]
+    fn buttons_released_with_last_device() {
+        let egui = EguiState::new(area());
+        egui.handle_device_added(&MOUSE);
+        egui.handle_pointer_button(MouseButton::Left, true);
+        egui.handle_device_removed(&MOUSE);
+        let events = std::mem::take(&mut egui.inner.lock().unwrap().events);
+        assert!(matches!(
+            events.as_slice(),
+            [
+                Event::PointerButton { pressed: true, .. },
+                Event::PointerButton { pressed: false, .. },
+                Event::PointerGone,
+            ]
+        ));
+    }
+
+    #[test]
+    fn buffer_follows_scale() {
+        let mut spec = BufferSpec::new(area(), 1.0);
+        assert_eq!(spec.buffer_size(), (400, 300).into());
+
+        for (scale, size) in [(2.0, (800, 600)), (1.5, (600, 450)), (1.0, (400, 300))] {
+            let new = BufferSpec::new(area(), scale);
+            assert_ne!(new, spec, "switching to scale {} keeps the buffer", scale);
+            assert_eq!(new.buffer_size(), size.into(), "scale {}", scale);
+            spec = new;
+        }
+    }
+
+    #[test]
+    fn buffer_follows_size() {
+        let resized = Rectangle::from_loc_and_size((100, 50), (401, 301));
+        let spec = BufferSpec::new(resized, 1.5);
+        assert_ne!(spec, BufferSpec::new(area(), 1.5));
+        assert_eq!(spec.buffer_size(), (602, 452).into());
+    }
+
+    // rendering itself needs a GL context, which unit tests don't have
+    #[test]
+    fn scale_switch_recreates_buffer() {
+        let egui = EguiState::new(area());
+        let mut inner = egui.inner.lock().unwrap();
+        assert!(inner.update_buffer_spec(area(), BufferSpec::new(area(), 1.0)));
+        assert!(!inner.update_buffer_spec(area(), BufferSpec::new(area(), 1.0)));
+
+        assert!(inner.update_buffer_spec(area(), BufferSpec::new(area(), 2.0)));
+        let spec = inner.buffer_spec.unwrap();
+        assert_eq!(spec.scale, 2.0);
+        assert_eq!(spec.buffer_size(), (800, 600).into());
+
+        let moved = Rectangle::from_loc_and_size((0, 0), area().size);
+        assert!(!inner.update_buffer_spec(moved, BufferSpec::new(moved, 2.0)));
+        assert_eq!(inner.area, moved);
+    }
+
+    #[test]
+    fn moving_keeps_buffer() {
+        for scale in SCALES {
+            let moved = Rectangle::from_loc_and_size((0, 0), area().size);
+            assert_eq!(
+                BufferSpec::new(moved, scale),
+                BufferSpec::new(area(), scale)
+            );
+        }
+    }
+
+    fn repeats(egui: &EguiState, time: u32) -> usize {
+        let mut inner = egui.inner.lock().unwrap();
+        inner.key_repeat(time);
+        inner
+            .events
+            .drain(..)
+            .filter(|event| matches!(event, Event::Key { repeat: true, .. }))
+            .count()
+    }
+
+    fn hold_backspace(egui: &EguiState, time: u32) {
+        let mut inner = egui.inner.lock().unwrap();
+        inner.start_key_repeat(Some(egui::Key::Backspace), Keycode::new(22), None, time);
+    }
+
+    #[test]
+    fn key_repeat_schedules_repaint() {
+        let egui = EguiState::new(area());
+        egui.set_key_repeat(200, 25);
+        // pretend the first frame was rendered
+        *egui.next_repaint.lock().unwrap() = None;
+        assert_eq!(egui.next_repaint(), RepaintDeadline::Never);
+
+        let before = Instant::now();
+        hold_backspace(&egui, 1000);
+        match egui.next_repaint() {
+            RepaintDeadline::At(deadline) => {
+                assert!(deadline >= before + Duration::from_millis(200));
+                assert!(deadline <= Instant::now() + Duration::from_millis(200));
+            }
+            deadline => panic!("unexpected deadline {:?}", deadline),
+        }
+
+        egui.handle_key_repeat(1000);
+        egui.inner.lock().unwrap().stop_key_repeat(Keycode::new(22));
+        assert_eq!(egui.next_repaint(), RepaintDeadline::Never);
+    }
+
+    #[test]
+    fn key_repeat_delay() {
+        let egui = EguiState::new(area());
+        egui.set_key_repeat(200, 25);
+        hold_backspace(&egui, 1000);
+        assert_eq!(repeats(&egui, 1100), 0);
+        assert_eq!(repeats(&egui, 1199), 0);
+        assert_eq!(repeats(&egui, 1200), 1);
+    }
+
+    #[test]
+    fn key_repeat_rate() {
+        let egui = EguiState::new(area());
+        egui.set_key_repeat(200, 25);
+        hold_backspace(&egui, 0);
+        assert_eq!(repeats(&egui, 200), 1);
+        assert_eq!(repeats(&egui, 239), 0);
+        assert_eq!(egui.handle_key_repeat(239), Some(240));
+        assert_eq!(repeats(&egui, 240), 1);
+        assert_eq!(repeats(&egui, 280), 1);
+    }
+
+    #[test]
+    fn key_repeat_does_not_burst() {
+        let egui = EguiState::new(area());
+        egui.set_key_repeat(200, 25);
+        hold_backspace(&egui, 0);
+        assert_eq!(repeats(&egui, 5000), 1);
+        assert_eq!(repeats(&egui, 5001), 0);
+        assert_eq!(egui.handle_key_repeat(5001), Some(5040));
+    }
+
+    #[test]
+    fn key_repeat_cancelled_on_release() {
+        let egui = EguiState::new(area());
+        hold_backspace(&egui, 0);
+        egui.inner.lock().unwrap().stop_key_repeat(Keycode::new(23));
+        assert_eq!(
+            repeats(&egui, 200),
+            1,
+            "releasing another key cancelled the repeat"
+        );
+        egui.inner.lock().unwrap().stop_key_repeat(Keycode::new(22));
+        assert_eq!(repeats(&egui, 1000), 0);
+        assert_eq!(egui.handle_key_repeat(1000), None);
+    }
+
+    #[test]
+    fn key_repeat_cancelled_on_modifier_change() {
+        let egui = EguiState::new(area());
+        hold_backspace(&egui, 0);
+        let unchanged = egui.inner.lock().unwrap().last_modifiers;
+        egui.inner.lock().unwrap().update_modifiers(unchanged);
+        assert_eq!(repeats(&egui, 200), 1);
+
+        let shift = ModifiersState {
+            shift: true,
+            ..Default::default()
+        };
+        egui.inner.lock().unwrap().update_modifiers(shift);
+        assert_eq!(repeats(&egui, 1000), 0);
+        assert_eq!(egui.handle_key_repeat(1000), None);
+    }
+
+    #[test]
+    fn key_repeat_disabled() {
+        let egui = EguiState::new(area());
+        egui.set_key_repeat(200, 0);
+        hold_backspace(&egui, 0);
+        assert_eq!(egui.handle_key_repeat(1000), None);
+    }
+
+    fn run_text_field(egui: &EguiState, text: &mut String) {
+        let mut inner = egui.inner.lock().unwrap();
+        let _ = egui.run_ui(&mut inner, area(), 1.0, None, |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.text_edit_singleline(text);
+            });
+        });
+    }
+
+    fn click_text_field(egui: &EguiState, text: &mut String) {
+        run_text_field(egui, text);
+        egui.handle_pointer_motion(area().loc.to_f64() + Point::from((20.0, 15.0)));
+        run_text_field(egui, text);
+        for pressed in [true, false] {
+            egui.handle_pointer_button(MouseButton::Left, pressed);
+            run_text_field(egui, text);
+        }
+    }
+
+    #[test]
+    fn click_without_selection_keeps_primary() {
+        let egui = EguiState::new(area());
+        let mut text = String::from("hello world");
+        click_text_field(&egui, &mut text);
+        assert!(egui.ctx.memory(|mem| mem.focused()).is_some());
+        run_text_field(&egui, &mut text);
+
+        let inner = egui.inner.lock().unwrap();
+        assert!(!inner.selections.query_primary);
+        assert_eq!(inner.selections.primary.text, None);
+        assert!(!inner.selections.primary.dirty);
+    }
+
+    #[test]
+    fn selecting_text_sets_primary() {
+        let egui = EguiState::new(area());
+        let mut text = String::from("hello world");
+        click_text_field(&egui, &mut text);
+        egui.inner.lock().unwrap().events.push(Event::Key {
+            key: egui::Key::A,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers: egui::Modifiers::COMMAND,
+        });
+        run_text_field(&egui, &mut text);
+        assert!(egui.inner.lock().unwrap().selections.query_primary);
+        run_text_field(&egui, &mut text);
+        let output = egui.last_output().unwrap();
+        assert!(output.copied_text.is_empty());
+
+        let inner = egui.inner.lock().unwrap();
+        assert_eq!(
+            inner.selections.primary.text.as_deref(),
+            Some("hello world")
+        );
+        assert_eq!(inner.selections.clipboard.text, None);
+    }
+
+    #[test]
+    fn url_handler_may_use_state() {
+        let egui = EguiState::new(area());
+        let opened = Arc::new(Mutex::new(Vec::new()));
+        let (state, urls) = (egui.clone(), opened.clone());
+        egui.set_url_handler(Some(Box::new(move |open_url: &egui::OpenUrl| {
+            // locks the state again
+            let _ = state.last_output();
+            urls.lock().unwrap().push(open_url.url.clone());
+        })));
+
+        let open_url = {
+            let mut inner = egui.inner.lock().unwrap();
+            let (_, open_url) = egui.run_ui(&mut inner, area(), 1.0, None, |ctx| {
+                ctx.open_url(egui::OpenUrl::same_tab("https://example.org"));
+            });
+            open_url
+        };
+        egui.open_url(&open_url.unwrap());
+        assert_eq!(*opened.lock().unwrap(), ["https://example.org"]);
+        assert!(egui.inner.lock().unwrap().url_handler.is_some());
+    }
+
+    #[cfg(feature = "desktop_integration")]
+    #[test]
+    fn space_focus_is_area_local() {
+        let egui = EguiState::new(area());
+        let mut space = smithay::desktop::Space::default();
+        space.map_element(egui.clone(), area().loc, false);
+        let run_window = |egui: &EguiState| {
+            let mut inner = egui.inner.lock().unwrap();
+            let _ = egui.run_ui(&mut inner, area(), 1.0, None, |ctx| {
+                egui::Window::new("window")
+                    .fixed_pos((0.0, 0.0))
+                    .fixed_size((200.0, 200.0))
+                    .show(ctx, |_| {});
+            });
+        };
+        run_window(&egui);
+
+        let global = Point::from((150.0, 80.0));
+        egui.handle_pointer_motion(global);
+        run_window(&egui);
+        assert!(egui.wants_pointer());
+
+        // a compositor passes the element and its location as focus to the seat
+        let (focus, location) = space.element_under(global).unwrap();
+        assert_eq!(focus, &egui);
+        assert_eq!(location, area().loc);
+        let event_location = global - location.to_f64();
+        assert!(space.element_under((400.0, 300.0)).is_none());
+
+        egui.handle_pointer_motion(egui.seat_location(event_location));
+        run_window(&egui);
+        let pos = egui.ctx.input(|i| i.pointer.latest_pos());
+        assert_eq!(pos, Some(Pos2::new(50.0, 30.0)));
+    }
+
+    fn wheel_events(egui: &EguiState) -> Vec<(egui::MouseWheelUnit, Vec2)> {
+        egui.inner
+            .lock()
+            .unwrap()
+            .events
+            .drain(..)
+            .filter_map(|event| match event {
+                Event::MouseWheel { unit, delta, .. } => Some((unit, delta)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn swipe_scrolls_like_axis() {
+        let egui = EguiState::new(area());
+        let frame = AxisFrame::new(0)
+            .source(AxisSource::Finger)
+            .value(Axis::Horizontal, 5.0)
+            .value(Axis::Vertical, 10.0);
+        egui.inner.lock().unwrap().axis_frame(&frame);
+        egui.inner.lock().unwrap().flush_axis();
+        let scrolled = wheel_events(&egui);
+
+        egui.inner.lock().unwrap().swipe((5.0, 10.0).into());
+        assert_eq!(wheel_events(&egui), scrolled);
+        assert_eq!(
+            scrolled,
+            [(egui::MouseWheelUnit::Point, Vec2::new(-5.0, -10.0))]
+        );
+    }
+
+    // a keyboard with a dead key, independent of the xkb data installed
+    const COMPOSE_KEYMAP: &str = r#"xkb_keymap {
+        xkb_keycodes "test" {
+            minimum = 8;
+            maximum = 255;
+            <AE12> = 21;
+            <AD03> = 26;
+            <RTRN> = 36;
+        };
+        xkb_types "test" {
+            type "ONE_LEVEL" {
+                modifiers = none;
+                level_name[Level1] = "Any";
+            };
+        };
+        xkb_compat "test" {};
+        xkb_symbols "test" {
+            key <AE12> { [ dead_acute ] };
+            key <AD03> { [ e ] };
+            key <RTRN> { [ Return ] };
+        };
+    };"#;
+
+    fn text_events(egui: &EguiState, code: u32) -> Vec<String> {
+        let mut inner = egui.inner.lock().unwrap();
+        inner.key_input(Keycode::new(code), true, None);
+        inner.key_input(Keycode::new(code), false, None);
+        inner
+            .events
+            .drain(..)
+            .filter_map(|event| match event {
+                Event::Text(text) => Some(text),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn compose_emits_text_once() {
+        let egui = EguiState::new(area());
+        {
+            let mut kbd = input::KbdInternal::from_string(COMPOSE_KEYMAP.into()).unwrap();
+            let context = xkbcommon::xkb::Context::new(xkbcommon::xkb::CONTEXT_NO_FLAGS);
+            let table = xkbcommon::xkb::compose::Table::new_from_buffer(
+                &context,
+                "<dead_acute> <e> : \"\u{e9}\" eacute\n",
+                "C",
+                xkbcommon::xkb::compose::FORMAT_TEXT_V1,
+                xkbcommon::xkb::compose::COMPILE_NO_FLAGS,
+            )
+            .unwrap();
+            kbd.set_compose_table(Some(&table));
+            egui.inner.lock().unwrap().kbd = Some(kbd);
+        }
+
+        assert!(text_events(&egui, 21).is_empty());
+        assert_eq!(text_events(&egui, 26), ["\u{e9}"]);
+        assert_eq!(text_events(&egui, 26), ["e"]);
+        // produces a carriage return, which egui expects as key only
+        assert!(text_events(&egui, 36).is_empty());
+    }
+
+    fn scroll(egui: &EguiState, frame: AxisFrame) {
+        egui.inner.lock().unwrap().axis_frame(&frame);
+    }
+
+    fn wheel(source: AxisSource, value: f64, v120: i32) -> AxisFrame {
+        AxisFrame::new(0)
+            .source(source)
+            .value(Axis::Vertical, value)
+            .v120(Axis::Vertical, v120)
+    }
+
+    #[test]
+    fn wheel_scrolls_lines() {
+        let egui = EguiState::new(area());
+        for source in [AxisSource::Wheel, AxisSource::WheelTilt] {
+            scroll(&egui, wheel(source, 15.0, 120));
+            egui.inner.lock().unwrap().flush_axis();
+            assert_eq!(
+                wheel_events(&egui),
+                [(egui::MouseWheelUnit::Line, Vec2::new(0.0, -1.0))],
+                "{:?}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn high_resolution_wheel_accumulates() {
+        let egui = EguiState::new(area());
+        for _ in 0..4 {
+            scroll(&egui, wheel(AxisSource::Wheel, 3.75, 30));
+        }
+        assert!(wheel_events(&egui).is_empty());
+        egui.inner.lock().unwrap().flush_axis();
+        assert_eq!(
+            wheel_events(&egui),
+            [(egui::MouseWheelUnit::Line, Vec2::new(0.0, -1.0))]
+        );
+    }
+
+    #[test]
+    fn continuous_scrolling_uses_points() {
+        let egui = EguiState::new(area());
+        for source in [AxisSource::Finger, AxisSource::Continuous] {
+            // v120 is only meaningful for wheels
+            scroll(&egui, wheel(source, 10.0, 120));
+            egui.inner.lock().unwrap().flush_axis();
+            assert_eq!(
+                wheel_events(&egui),
+                [(egui::MouseWheelUnit::Point, Vec2::new(0.0, -10.0))],
+                "{:?}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn scrolling_accumulates_until_frame() {
+        let egui = EguiState::new(area());
+        scroll(&egui, wheel(AxisSource::Finger, 2.0, 0));
+        scroll(
+            &egui,
+            AxisFrame::new(0)
+                .source(AxisSource::Finger)
+                .value(Axis::Horizontal, 4.0)
+                .value(Axis::Vertical, 3.0),
+        );
+        assert!(wheel_events(&egui).is_empty());
+        egui.inner.lock().unwrap().flush_axis();
+        assert_eq!(
+            wheel_events(&egui),
+            [(egui::MouseWheelUnit::Point, Vec2::new(-4.0, -5.0))]
+        );
+
+        // a different unit or a stop ends the accumulation early
+        scroll(&egui, wheel(AxisSource::Finger, 2.0, 0));
+        scroll(&egui, wheel(AxisSource::Wheel, 15.0, 120));
+        assert_eq!(
+            wheel_events(&egui),
+            [(egui::MouseWheelUnit::Point, Vec2::new(0.0, -2.0))]
+        );
+        scroll(&egui, wheel(AxisSource::Wheel, 0.0, 0).stop(Axis::Vertical));
+        assert_eq!(
+            wheel_events(&egui),
+            [(egui::MouseWheelUnit::Line, Vec2::new(0.0, -1.0))]
+        );
+    }
+}
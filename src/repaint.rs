@@ -0,0 +1,71 @@
+// This file schedules egui's repaints on a calloop event loop, see lib.rs for usage
+
+use smithay::reexports::calloop::{
+    timer::{TimeoutAction, Timer},
+    LoopHandle, RegistrationToken,
+};
+
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use crate::{EguiState, RepaintDeadline};
+
+/// Timer on a calloop event loop, firing whenever an [`EguiState`] needs a new frame
+///
+/// Call [`RepaintTimer::schedule`] after every [`EguiState::render`] call
+/// and the provided callback is invoked once the next frame is due.
+pub struct RepaintTimer<'l, D> {
+    handle: LoopHandle<'l, D>,
+    callback: Rc<RefCell<dyn FnMut(&mut D) + 'l>>,
+    timer: Option<RegistrationToken>,
+}
+
+impl<D> fmt::Debug for RepaintTimer<'_, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RepaintTimer")
+            .field("timer", &self.timer)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'l, D> RepaintTimer<'l, D> {
+    /// Creates a new `RepaintTimer` invoking `callback` on the event loop of `handle`
+    pub fn new(handle: LoopHandle<'l, D>, callback: impl FnMut(&mut D) + 'l) -> Self {
+        RepaintTimer {
+            handle,
+            callback: Rc::new(RefCell::new(callback)),
+            timer: None,
+        }
+    }
+
+    /// Replace the pending timer with one firing at the [next repaint](EguiState::next_repaint) of `egui`
+    pub fn schedule(&mut self, egui: &EguiState) {
+        self.cancel();
+        let timer = match egui.next_repaint() {
+            RepaintDeadline::Immediately => Timer::immediate(),
+            RepaintDeadline::At(deadline) => Timer::from_deadline(deadline),
+            RepaintDeadline::Never => return,
+        };
+
+        let callback = self.callback.clone();
+        match self.handle.insert_source(timer, move |_, _, data| {
+            (callback.borrow_mut())(data);
+            TimeoutAction::Drop
+        }) {
+            Ok(token) => self.timer = Some(token),
+            Err(err) => log::warn!("Failed to schedule egui repaint: {}", err),
+        }
+    }
+
+    /// Remove the pending timer, if any
+    pub fn cancel(&mut self) {
+        if let Some(token) = self.timer.take() {
+            self.handle.remove(token);
+        }
+    }
+}
+
+impl<D> Drop for RepaintTimer<'_, D> {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}